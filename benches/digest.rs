@@ -0,0 +1,29 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use oci_image_spec::image_digest::algorithm::{
+    Algorithms, CryptoHash, DEFAULT_BUFFER_SIZE, SHA256,
+};
+
+fn from_file(c: &mut Criterion) {
+    let size = 64 * 1024 * 1024;
+    let path = std::env::temp_dir().join("oci-image-spec-bench-blob");
+    std::fs::write(&path, vec![0u8; size]).unwrap();
+    let path = path.to_str().unwrap();
+    let alg = Algorithms::new().get_algorithm(SHA256).unwrap();
+
+    let mut group = c.benchmark_group("from_file");
+    group.throughput(Throughput::Bytes(size as u64));
+    for buffer_size in [1024, DEFAULT_BUFFER_SIZE] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(buffer_size),
+            &buffer_size,
+            |b, buffer_size| b.iter(|| alg.from_file_with_buffer(path, *buffer_size).unwrap()),
+        );
+    }
+    #[cfg(feature = "mmap")]
+    group.bench_function("mmap", |b| b.iter(|| alg.from_file_mmap(path).unwrap()));
+    group.finish();
+    std::fs::remove_file(path).unwrap();
+}
+
+criterion_group!(benches, from_file);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+
+/// Clock tells the current time. Time-dependent helpers take a Clock so that
+/// tests can control the time they observe.
+pub trait Clock {
+    /// now returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// SystemClock is the Clock of the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// FixedClock is a Clock which only moves when told to, for deterministic tests.
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug, Default)]
+pub struct FixedClock {
+    now: std::sync::Mutex<DateTime<Utc>>,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl FixedClock {
+    /// new returns a clock stopped at `now`.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        FixedClock {
+            now: std::sync::Mutex::new(now),
+        }
+    }
+
+    /// set moves the clock to `now`.
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// advance moves the clock forward by `duration`.
+    pub fn advance(&self, duration: chrono::Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
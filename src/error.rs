@@ -0,0 +1,94 @@
+use crate::image_digest::digest::DigestParseError;
+use crate::image_digest::digestset::LookupError;
+use crate::specs::v1::descriptor::VerifyError;
+use crate::specs::v1::validation::ValidationReport;
+use std::io::ErrorKind;
+
+/// Error is the error returned by the fallible functions of this crate.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// An I/O operation failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// A document couldn't be encoded or decoded as JSON.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// A digest is malformed or uses an unsupported algorithm.
+    #[error(transparent)]
+    Digest(#[from] DigestParseError),
+
+    /// Content doesn't match its descriptor.
+    #[error(transparent)]
+    Verify(#[from] VerifyError),
+
+    /// A short digest couldn't be resolved.
+    #[error(transparent)]
+    Lookup(#[from] LookupError),
+
+    /// A document doesn't conform to the specification.
+    #[error("validation failed: {0}")]
+    Validation(ValidationReport),
+
+    /// A media type isn't allowed by a media type policy.
+    #[error("media type {0} is not allowed")]
+    MediaTypeNotAllowed(String),
+
+    /// A format or encoding isn't supported.
+    #[error("{0}")]
+    Unsupported(String),
+
+    /// An argument is invalid, e.g. an annotation namespace.
+    #[error("{0}")]
+    InvalidInput(String),
+
+    /// A document or blob is malformed or inconsistent.
+    #[error("{0}")]
+    InvalidData(String),
+}
+
+/// Result is a `std::result::Result` with this crate's Error.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<Error> for std::io::Error {
+    /// from maps the error to the closest `std::io::ErrorKind`, so it can be
+    /// returned from `Read` and `Write` implementations.
+    fn from(err: Error) -> Self {
+        if let Error::Io(err) = err {
+            return err;
+        }
+        let kind = match &err {
+            Error::Digest(DigestParseError::UnknownAlgorithm(_)) | Error::Unsupported(_) => {
+                ErrorKind::Unsupported
+            }
+            Error::Lookup(LookupError::NotFound) => ErrorKind::NotFound,
+            Error::Verify(VerifyError::SizeMismatch { .. }) => ErrorKind::UnexpectedEof,
+            Error::MediaTypeNotAllowed(_) => ErrorKind::PermissionDenied,
+            Error::Lookup(_) | Error::InvalidInput(_) => ErrorKind::InvalidInput,
+            _ => ErrorKind::InvalidData,
+        };
+        std::io::Error::new(kind, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_io_error() {
+        let err: std::io::Error = Error::from(DigestParseError::InvalidFormat).into();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let err: std::io::Error =
+            Error::from(DigestParseError::UnknownAlgorithm("md5".to_string())).into();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert_eq!(err.to_string(), "unsupported digest algorithm \"md5\"");
+        let err: std::io::Error = Error::from(LookupError::NotFound).into();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let io = std::io::Error::new(ErrorKind::BrokenPipe, "closed");
+        let err: std::io::Error = Error::from(io).into();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::error::Error;
+use crate::image_digest::algorithm::{algorithms, CryptoHash, CANONICAL};
+use crate::image_digest::digest::{Digest, DigestParseError};
+use crate::specs::v1::manifest::Manifest;
+use crate::specs::v1::mediatype::MEDIA_TYPE_IMAGE_MANIFEST;
+
+/// BlobReference records the identity of a blob referenced by a manifest.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BlobReference {
+    /// MediaType is the media type of the blob.
+    #[serde(rename = "mediaType", skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+
+    /// Digest is the digest of the blob.
+    #[serde(rename = "digest")]
+    pub digest: String,
+
+    /// Size is the size of the blob, in bytes.
+    #[serde(rename = "size")]
+    pub size: u64,
+}
+
+/// VerificationBundle is a self-contained statement about an image: the exact
+/// manifest and config documents plus the digests and sizes of every blob they
+/// reference, without any layer content. It is meant to be signed and later
+/// used to verify the image offline.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct VerificationBundle {
+    /// ManifestDigest is the digest of the embedded manifest.
+    #[serde(rename = "manifestDigest")]
+    pub manifest_digest: String,
+
+    /// Manifest is the manifest document, byte for byte.
+    #[serde(rename = "manifest")]
+    pub manifest: String,
+
+    /// Config is the config document, byte for byte.
+    #[serde(rename = "config")]
+    pub config: String,
+
+    /// Blobs lists the config and layer blobs referenced by the manifest.
+    #[serde(rename = "blobs")]
+    pub blobs: Vec<BlobReference>,
+}
+
+/// verification_bundle builds a VerificationBundle from the raw manifest and
+/// config documents. The config must match the manifest's config descriptor.
+pub fn verification_bundle(manifest: &[u8], config: &[u8]) -> Result<VerificationBundle, Error> {
+    let parsed: Manifest = serde_json::from_slice(manifest)?;
+    if let Some(media_type) = &parsed.media_type {
+        if media_type != MEDIA_TYPE_IMAGE_MANIFEST {
+            return Err(Error::InvalidData(format!(
+                "unexpected manifest media type {}",
+                media_type
+            )));
+        }
+    }
+
+    let bundle = VerificationBundle {
+        manifest_digest: digest_bytes(CANONICAL, manifest)?,
+        manifest: to_utf8(manifest)?,
+        config: to_utf8(config)?,
+        blobs: std::iter::once(&parsed.config)
+            .chain(parsed.layers.iter())
+            .map(|descriptor| BlobReference {
+                media_type: descriptor.media_type.clone(),
+                digest: descriptor
+                    .digest
+                    .as_ref()
+                    .map(Digest::to_string)
+                    .unwrap_or_default(),
+                size: descriptor.size,
+            })
+            .collect(),
+    };
+    bundle.verify()?;
+    Ok(bundle)
+}
+
+impl VerificationBundle {
+    /// verify checks that the embedded documents are consistent with the
+    /// recorded manifest digest and the manifest's config descriptor.
+    pub fn verify(&self) -> Result<(), Error> {
+        let algorithm = self
+            .manifest_digest
+            .split_once(':')
+            .map(|(algorithm, _)| algorithm)
+            .unwrap_or_default();
+        if digest_bytes(algorithm, self.manifest.as_bytes())? != self.manifest_digest {
+            return Err(Error::InvalidData(
+                "manifest does not match the manifest digest".to_string(),
+            ));
+        }
+
+        let manifest: Manifest = serde_json::from_str(&self.manifest)?;
+        let expected = manifest
+            .config
+            .digest
+            .map(|digest| digest.to_string())
+            .unwrap_or_default();
+        let algorithm = expected
+            .split_once(':')
+            .map(|(algorithm, _)| algorithm)
+            .unwrap_or_default();
+        if digest_bytes(algorithm, self.config.as_bytes())? != expected
+            || self.config.len() as u64 != manifest.config.size
+        {
+            return Err(Error::InvalidData(
+                "config does not match the manifest config descriptor".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn to_utf8(bytes: &[u8]) -> Result<String, Error> {
+    String::from_utf8(bytes.to_vec()).map_err(|err| Error::InvalidData(err.to_string()))
+}
+
+// digest_bytes returns the `algorithm:encoded` digest of bytes.
+fn digest_bytes(algorithm: &str, bytes: &[u8]) -> Result<String, Error> {
+    let algorithm = algorithms()
+        .get_algorithm(algorithm)
+        .ok_or_else(|| DigestParseError::UnknownAlgorithm(algorithm.to_string()))?;
+    Ok(format!("{}:{}", algorithm, algorithm.from_bytes(bytes)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image_digest::algorithm::SHA256;
+
+    #[test]
+    fn test_verification_bundle() {
+        let config =
+            br#"{"architecture":"amd64","os":"linux","rootfs":{"type":"layers","diff_ids":[]}}"#;
+        let manifest = format!(
+            r#"{{"schemaVersion":2,"config":{{"mediaType":"application/vnd.oci.image.config.v1+json","digest":"{}","size":{}}},"layers":[]}}"#,
+            digest_bytes(SHA256, config).unwrap(),
+            config.len()
+        );
+        let bundle = verification_bundle(manifest.as_bytes(), config).unwrap();
+        assert_eq!(bundle.blobs.len(), 1);
+        assert_eq!(bundle.manifest, manifest);
+
+        assert!(verification_bundle(manifest.as_bytes(), b"{}").is_err());
+    }
+}
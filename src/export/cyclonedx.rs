@@ -0,0 +1,250 @@
+use crate::image_digest::algorithm::{BLAKE3, SHA256, SHA384, SHA3_256, SHA3_512, SHA512};
+use crate::specs::v1::annotations::*;
+use crate::specs::v1::config::Image;
+use crate::specs::v1::manifest::Manifest;
+
+/// Component is a CycloneDX component entry of type `container` describing an image.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Component {
+    /// Type is the CycloneDX component type, always `container` for images.
+    #[serde(rename = "type")]
+    pub type_: String,
+
+    /// Name is the repository name of the image.
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Version is the image version annotation or the tag the image was referenced by.
+    #[serde(rename = "version", skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Description is the human-readable description of the image.
+    #[serde(rename = "description", skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Author is the author of the image.
+    #[serde(rename = "author", skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+
+    /// Purl is the `pkg:oci` package URL of the image, pinned by manifest digest.
+    #[serde(rename = "purl")]
+    pub purl: String,
+
+    /// Hashes contains the manifest digest.
+    #[serde(rename = "hashes", skip_serializing_if = "Vec::is_empty")]
+    pub hashes: Vec<Hash>,
+
+    /// Licenses contains the SPDX license expression of the image, if any.
+    #[serde(rename = "licenses", skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<License>,
+
+    /// Properties contains the manifest annotations as name/value pairs.
+    #[serde(rename = "properties", skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<Property>,
+}
+
+/// Hash is a CycloneDX hash entry.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Hash {
+    /// Alg is the CycloneDX name of the hash algorithm, e.g. `SHA-256`.
+    #[serde(rename = "alg")]
+    pub alg: String,
+
+    /// Content is the hex encoded hash value.
+    #[serde(rename = "content")]
+    pub content: String,
+}
+
+/// License is a CycloneDX license entry holding an SPDX license expression.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct License {
+    /// Expression is the SPDX license expression, e.g. `Apache-2.0`.
+    #[serde(rename = "expression")]
+    pub expression: String,
+}
+
+/// Property is a CycloneDX name/value property.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Property {
+    /// Name is the name of the property, e.g. an annotation key.
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Value is the value of the property.
+    #[serde(rename = "value")]
+    pub value: String,
+}
+
+/// container_component builds a CycloneDX `container` component for the image
+/// identified by `manifest_digest` in `repository` (e.g. `docker.io/library/debian`).
+/// Version, description and licenses are taken from the well-known
+/// `org.opencontainers.image.*` manifest annotations, or from the config labels
+/// of the same name when the manifest doesn't set them.
+pub fn container_component(
+    repository: &str,
+    tag: Option<&str>,
+    manifest_digest: &str,
+    manifest: &Manifest,
+    config: &Image,
+) -> Component {
+    let lookup = |key: &str| -> Option<String> {
+        manifest
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(key))
+            .or_else(|| {
+                config
+                    .config
+                    .as_ref()
+                    .and_then(|config| config.labels.as_ref())
+                    .and_then(|labels| labels.get(key))
+            })
+            .cloned()
+    };
+
+    let name = repository
+        .rsplit('/')
+        .next()
+        .unwrap_or(repository)
+        .to_lowercase();
+
+    let mut qualifiers = vec![
+        ("arch", config.architecture.as_str()),
+        ("os", config.os.as_str()),
+        ("repository_url", repository),
+    ];
+    if let Some(tag) = tag {
+        qualifiers.push(("tag", tag));
+    }
+    let qualifiers = qualifiers
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(key, value)| format!("{}={}", key, percent_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let purl = format!(
+        "pkg:oci/{}@{}?{}",
+        name,
+        percent_encode(manifest_digest),
+        qualifiers
+    );
+
+    let hashes = match manifest_digest.split_once(':') {
+        Some((algorithm, encoded)) => match hash_algorithm(algorithm) {
+            Some(alg) => vec![Hash {
+                alg: alg.to_string(),
+                content: encoded.to_string(),
+            }],
+            None => vec![],
+        },
+        None => vec![],
+    };
+
+    let licenses = lookup(ANNOTATION_LICENSES)
+        .map(|expression| vec![License { expression }])
+        .unwrap_or_default();
+
+    let mut properties = manifest
+        .annotations
+        .as_ref()
+        .map(|annotations| {
+            annotations
+                .iter()
+                .map(|(name, value)| Property {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    properties.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Component {
+        type_: "container".to_string(),
+        name,
+        version: lookup(ANNOTATION_VERSION).or_else(|| tag.map(|tag| tag.to_string())),
+        description: lookup(ANNOTATION_DESCRIPTION),
+        author: config.author.clone().or_else(|| lookup(ANNOTATION_AUTHORS)),
+        purl,
+        hashes,
+        licenses,
+        properties,
+    }
+}
+
+// hash_algorithm maps a digest algorithm to its CycloneDX hash algorithm name.
+fn hash_algorithm(algorithm: &str) -> Option<&'static str> {
+    match algorithm {
+        SHA256 => Some("SHA-256"),
+        SHA384 => Some("SHA-384"),
+        SHA512 => Some("SHA-512"),
+        SHA3_256 => Some("SHA3-256"),
+        SHA3_512 => Some("SHA3-512"),
+        BLAKE3 => Some("BLAKE3"),
+        _ => None,
+    }
+}
+
+// percent_encode encodes the characters not allowed verbatim in purl versions and qualifier values.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_component() {
+        let mut annotations = std::collections::HashMap::new();
+        annotations.insert(ANNOTATION_LICENSES.to_string(), "MIT".to_string());
+        let manifest = Manifest {
+            annotations: Some(annotations),
+            ..Default::default()
+        };
+        let config = Image {
+            architecture: "amd64".to_string(),
+            os: "linux".to_string(),
+            ..Default::default()
+        };
+        let digest = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let component = container_component(
+            "docker.io/library/Hello",
+            Some("latest"),
+            digest,
+            &manifest,
+            &config,
+        );
+        assert_eq!(
+            component.purl,
+            "pkg:oci/hello@sha256%3A2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824?arch=amd64&os=linux&repository_url=docker.io/library/Hello&tag=latest"
+        );
+        assert_eq!(component.version, Some("latest".to_string()));
+        assert_eq!(component.hashes[0].alg, "SHA-256");
+        assert_eq!(component.licenses[0].expression, "MIT");
+        assert_eq!(component.properties[0].name, ANNOTATION_LICENSES);
+
+        let mut labels = std::collections::HashMap::new();
+        labels.insert(ANNOTATION_VERSION.to_string(), "1.2.3".to_string());
+        labels.insert(ANNOTATION_LICENSES.to_string(), "Apache-2.0".to_string());
+        let config = Image {
+            config: Some(crate::specs::v1::config::ImageConfig {
+                labels: Some(labels),
+                ..Default::default()
+            }),
+            ..config
+        };
+        let component =
+            container_component("docker.io/library/hello", None, digest, &manifest, &config);
+        assert_eq!(component.version, Some("1.2.3".to_string()));
+        assert_eq!(component.licenses[0].expression, "MIT");
+    }
+}
@@ -0,0 +1,4 @@
+pub mod bundle;
+pub mod cyclonedx;
+pub mod ndjson;
+pub mod signing;
@@ -0,0 +1,140 @@
+use crate::error::Error;
+use crate::image_digest::digest::Digest;
+use crate::specs::v1::descriptor::{Descriptor, Platform};
+use crate::specs::v1::index::Index;
+use crate::specs::v1::manifest::Manifest;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Record is a flattened descriptor, written as one line of newline-delimited JSON.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Record {
+    /// Parent is the digest of the index or manifest holding the descriptor.
+    #[serde(rename = "parent")]
+    pub parent: String,
+
+    /// Role is the position of the descriptor in its parent: `manifest`, `config` or `layer`.
+    #[serde(rename = "role")]
+    pub role: String,
+
+    /// MediaType is the media type of the described content.
+    #[serde(rename = "mediaType", skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+
+    /// Digest is the digest of the described content.
+    #[serde(rename = "digest", skip_serializing_if = "Option::is_none")]
+    pub digest: Option<Digest>,
+
+    /// Size is the size of the described content, in bytes.
+    #[serde(rename = "size")]
+    pub size: u64,
+
+    /// Platform is the platform of the descriptor, or of the manifest holding it.
+    #[serde(rename = "platform", skip_serializing_if = "Option::is_none")]
+    pub platform: Option<Platform>,
+
+    /// Annotations are the annotations of the descriptor.
+    #[serde(rename = "annotations", skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<HashMap<String, String>>,
+}
+
+impl Record {
+    fn new(parent: &str, role: &str, descriptor: &Descriptor, platform: Option<&Platform>) -> Self {
+        Record {
+            parent: parent.to_string(),
+            role: role.to_string(),
+            media_type: descriptor.media_type.clone(),
+            digest: descriptor.digest.clone(),
+            size: descriptor.size,
+            platform: descriptor.platform.as_ref().or(platform).cloned(),
+            annotations: descriptor.annotations.clone(),
+        }
+    }
+}
+
+/// write_index writes one record per manifest of the index identified by
+/// `index_digest`. For each manifest `resolve` returns, records for its config
+/// and layers are written as well; pass `|_| None` to export the index only.
+pub fn write_index<W, F>(
+    mut writer: W,
+    index_digest: &str,
+    index: &Index,
+    mut resolve: F,
+) -> Result<(), Error>
+where
+    W: Write,
+    F: FnMut(&Descriptor) -> Option<Manifest>,
+{
+    for descriptor in &index.manifests {
+        write_record(
+            &mut writer,
+            &Record::new(index_digest, "manifest", descriptor, None),
+        )?;
+        let manifest = match resolve(descriptor) {
+            Some(manifest) => manifest,
+            None => continue,
+        };
+        let parent = descriptor
+            .digest
+            .as_ref()
+            .map(Digest::to_string)
+            .unwrap_or_default();
+        let platform = descriptor.platform.as_ref();
+        write_record(
+            &mut writer,
+            &Record::new(&parent, "config", &manifest.config, platform),
+        )?;
+        for layer in &manifest.layers {
+            write_record(&mut writer, &Record::new(&parent, "layer", layer, platform))?;
+        }
+    }
+    Ok(writer.flush()?)
+}
+
+fn write_record<W: Write>(writer: &mut W, record: &Record) -> Result<(), Error> {
+    serde_json::to_writer(&mut *writer, record)?;
+    Ok(writer.write_all(b"\n")?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_index() {
+        let index = Index {
+            manifests: vec![Descriptor {
+                digest: Some(format!("sha256:{}", "a".repeat(64)).parse().unwrap()),
+                size: 10,
+                platform: Some(Platform {
+                    architecture: "amd64".to_string(),
+                    os: "linux".to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let manifest = Manifest {
+            layers: vec![Descriptor::default(), Descriptor::default()],
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        write_index(&mut out, "sha256:ffff", &index, |_| None).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1);
+
+        let mut out = Vec::new();
+        write_index(&mut out, "sha256:ffff", &index, |_| Some(manifest.clone())).unwrap();
+        let records = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Record>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].parent, "sha256:ffff");
+        assert_eq!(records[3].role, "layer");
+        assert_eq!(records[3].parent, format!("sha256:{}", "a".repeat(64)));
+        assert_eq!(records[3].platform.as_ref().unwrap().os, "linux");
+    }
+}
@@ -0,0 +1,156 @@
+use crate::error::Error;
+use crate::specs::v1::descriptor::Descriptor;
+use std::collections::BTreeMap;
+
+/// SIMPLE_SIGNING_TYPE is the critical type of cosign container image signatures.
+pub const SIMPLE_SIGNING_TYPE: &str = "cosign container image signature";
+
+/// Identity identifies the repository the signed image belongs to.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Identity {
+    /// DockerReference is the reference of the repository, e.g. `registry.example.com/app`.
+    #[serde(rename = "docker-reference")]
+    pub docker_reference: String,
+}
+
+/// SignedImage identifies the signed manifest.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SignedImage {
+    /// DockerManifestDigest is the digest of the signed manifest.
+    #[serde(rename = "docker-manifest-digest")]
+    pub docker_manifest_digest: String,
+}
+
+/// Critical holds the fields a verifier must check before trusting a signature.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Critical {
+    /// Identity is the repository the signature is valid for.
+    #[serde(rename = "identity")]
+    pub identity: Identity,
+
+    /// Image is the manifest the signature is valid for.
+    #[serde(rename = "image")]
+    pub image: SignedImage,
+
+    /// Type is the kind of signature, SIMPLE_SIGNING_TYPE.
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+/// SimpleSigning is the "simple signing" payload signed by cosign and
+/// containers/image. Fields are declared in key order and optional entries are
+/// kept sorted, so the serialized payload is canonical.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SimpleSigning {
+    /// Critical holds the claims checked during verification.
+    #[serde(rename = "critical")]
+    pub critical: Critical,
+
+    /// Optional holds arbitrary claims, such as annotations, which aren't
+    /// checked during verification.
+    #[serde(rename = "optional", skip_serializing_if = "Option::is_none")]
+    pub optional: Option<BTreeMap<String, serde_json::Value>>,
+}
+
+impl SimpleSigning {
+    /// new returns the payload signing the manifest referenced by descriptor
+    /// in the given repository, e.g. `registry.example.com/app`. The
+    /// annotations of descriptor become the optional claims.
+    pub fn new(repository: &str, descriptor: &Descriptor) -> Result<Self, Error> {
+        Ok(SimpleSigning {
+            critical: Critical {
+                identity: Identity {
+                    docker_reference: repository.to_string(),
+                },
+                image: SignedImage {
+                    docker_manifest_digest: descriptor_digest(descriptor)?.to_string(),
+                },
+                type_: SIMPLE_SIGNING_TYPE.to_string(),
+            },
+            optional: descriptor.annotations.as_ref().map(|annotations| {
+                annotations
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone().into()))
+                    .collect()
+            }),
+        })
+    }
+
+    /// payload returns the canonical JSON encoding of the payload, ready to be signed.
+    pub fn payload(&self) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// verify checks that the payload signs the manifest referenced by
+    /// descriptor in the given repository.
+    pub fn verify(&self, repository: &str, descriptor: &Descriptor) -> Result<(), Error> {
+        if self.critical.type_ != SIMPLE_SIGNING_TYPE {
+            return Err(Error::InvalidData(format!(
+                "unexpected signature type {}",
+                self.critical.type_
+            )));
+        }
+        if self.critical.identity.docker_reference != repository {
+            return Err(Error::InvalidData(format!(
+                "signature is for repository {}, not {}",
+                self.critical.identity.docker_reference, repository
+            )));
+        }
+        if self.critical.image.docker_manifest_digest != descriptor_digest(descriptor)? {
+            return Err(Error::InvalidData(format!(
+                "signature is for manifest {}",
+                self.critical.image.docker_manifest_digest
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn descriptor_digest(descriptor: &Descriptor) -> Result<&str, Error> {
+    descriptor
+        .digest
+        .as_ref()
+        .map(|digest| digest.digest.as_str())
+        .ok_or_else(|| Error::InvalidInput("descriptor has no digest".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_signing() {
+        let digest = format!("sha256:{}", "a".repeat(64));
+        let descriptor = Descriptor {
+            digest: Some(digest.parse().unwrap()),
+            annotations: Some(std::collections::HashMap::from([
+                ("z".to_string(), "1".to_string()),
+                ("a".to_string(), "2".to_string()),
+            ])),
+            ..Default::default()
+        };
+        let signing = SimpleSigning::new("example.com/app", &descriptor).unwrap();
+        assert_eq!(
+            String::from_utf8(signing.payload().unwrap()).unwrap(),
+            format!(
+                r#"{{"critical":{{"identity":{{"docker-reference":"example.com/app"}},"image":{{"docker-manifest-digest":"{}"}},"type":"cosign container image signature"}},"optional":{{"a":"2","z":"1"}}}}"#,
+                digest
+            )
+        );
+
+        let parsed: SimpleSigning = serde_json::from_slice(&signing.payload().unwrap()).unwrap();
+        assert!(parsed.verify("example.com/app", &descriptor).is_ok());
+        assert!(parsed.verify("example.com/other", &descriptor).is_err());
+        assert!(SimpleSigning::new("example.com/app", &Descriptor::default()).is_err());
+
+        let descriptor = Descriptor {
+            annotations: None,
+            ..descriptor
+        };
+        let signing = SimpleSigning::new("example.com/app", &descriptor).unwrap();
+        assert_eq!(signing.optional, None);
+        assert!(!String::from_utf8(signing.payload().unwrap())
+            .unwrap()
+            .contains("optional"));
+    }
+}
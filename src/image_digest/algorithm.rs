@@ -1,6 +1,11 @@
+use super::digest::DigestParseError;
+use super::digester::Digester;
+use crate::error::Error;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::io::{Error, ErrorKind};
+use std::io::{BufRead, BufReader, Read};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 /// SHA256 with hex encoding (lower case only)
 pub const SHA256: &str = "sha256";
@@ -10,315 +15,550 @@ pub const SHA384: &str = "sha384";
 pub const SHA512: &str = "sha512";
 /// BLAKE3 with hex encoding (lower case only)
 pub const BLAKE3: &str = "blake3";
+/// SHA512_256, SHA-512 truncated to 256 bits, with hex encoding (lower case only)
+pub const SHA512_256: &str = "sha512/256";
+/// SHA3_256 with hex encoding (lower case only)
+pub const SHA3_256: &str = "sha3-256";
+/// SHA3_512 with hex encoding (lower case only)
+pub const SHA3_512: &str = "sha3-512";
 
 // CANONICAL is the primary digest algorithm used with the distribution
 // project. Other digests may be used but this one is the primary storage
 // digest.
 pub const CANONICAL: &str = SHA256;
 
+// ALGORITHM_REGEX matches the algorithm component of the digest grammar.
+static ALGORITHM_REGEX: Lazy<regex::Regex> =
+    Lazy::new(|| regex::Regex::new(r"^[a-z0-9]+(?:[./+_-][a-z0-9]+)*$").unwrap());
+
+/// DEFAULT_BUFFER_SIZE is the size of the chunks read by from_reader and from_file.
+pub const DEFAULT_BUFFER_SIZE: usize = 128 * 1024;
+
+/// DEFAULT_BLAKE3_PARALLEL_THRESHOLD is the input size from which BLAKE3 hashes
+/// with multiple threads when the rayon feature is enabled.
+pub const DEFAULT_BLAKE3_PARALLEL_THRESHOLD: usize = 1024 * 1024;
+
+use digest::generic_array::{typenum::U32, GenericArray};
 use digest::DynDigest;
-use sha2::{Digest, Sha256, Sha384, Sha512};
+use sha2::{Digest, Sha256, Sha384, Sha512, Sha512Trunc256};
+use sha3::{Sha3_256, Sha3_512};
 
-/// CryptoHash is the interface that any hash algorithm must implement
+/// CryptoHash is the interface that any hash algorithm must implement. It is
+/// object safe; the methods generic over readers aren't available on `dyn CryptoHash`.
 pub trait CryptoHash {
     // available reports whether the given hash function is usable in the current binary.
-    fn available(self) -> bool;
-    // size returns the length, in bytes, of a digest resulting from the given hash function.
-    fn size(self) -> isize;
+    fn available(&self) -> bool;
+    // size returns the length, in bits, of a digest resulting from the given hash function.
+    fn size(&self) -> isize;
     // string returns the name of the hash function.
-    fn string(self) -> &'static str;
+    fn string(&self) -> &str;
     // set implemented to allow use of Algorithm as a command line flag.
-    fn set(self, _: &str) -> Result<Self, Error>
+    fn set(&self, _: &str) -> Result<Self, Error>
     where
         Self: Sized;
-    // digester returns a new digester for the specified algorithm. If the algorithm
-    // does not have a digester implementation, nil will be returned. This can be
-    // checked by calling Available before calling Digester.
-    fn digester(&self) -> Box<dyn DynDigest>;
-    // hash returns a new hash as used by the algorithm. If not available, the
-    // method will panic. Check Algorithm.Available() before calling.
-    fn hash(self) -> Box<dyn DynDigest>;
+    // digester returns a new digester for the specified algorithm. It fails if
+    // the algorithm does not have a digester implementation, which can be
+    // checked by calling available before calling digester.
+    fn digester(&self) -> Result<Box<dyn DynDigest>, Error>;
+    // hash returns a new hash as used by the algorithm. It fails if the
+    // algorithm isn't available.
+    fn hash(&self) -> Result<Box<dyn DynDigest>, Error>;
     // encode encodes the raw bytes of a digest, typically from a hash.Hash, into
     // the encoded portion of the digest.
-    fn encode(&self, _: &[u8]) -> String;
+    fn encode(&self, _: &[u8]) -> Result<String, Error>;
     // from_reader returns the digest of the reader using the algorithm.
-    fn from_reader<R: std::io::Read>(&self, _: R) -> String;
+    fn from_reader<R: std::io::Read>(&self, _: R) -> Result<String, Error>
+    where
+        Self: Sized;
+    // from_reader_with_buffer returns the digest of the reader, read in chunks of buffer_size bytes.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_reader_with_buffer<R: std::io::Read>(
+        &self,
+        _: R,
+        buffer_size: usize,
+    ) -> Result<String, Error>
+    where
+        Self: Sized;
     // from_bytes digests the input and returns a Digest.
-    fn from_bytes(&self, _: &[u8]) -> String;
+    fn from_bytes(&self, _: &[u8]) -> Result<String, Error>;
     // from_string digests the string input and returns a Digest.
-    fn from_string(&self, _: &str) -> String;
+    fn from_string(&self, _: &str) -> Result<String, Error>;
     // from_file digests the string input and returns a Digest.
     fn from_file(&self, _: &str) -> Result<String, Error>;
+    // from_file_with_buffer digests the file, read in chunks of buffer_size bytes.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_file_with_buffer(&self, _: &str, buffer_size: usize) -> Result<String, Error>;
     // Validate validates the encoded portion string
     fn validate(&self, _: &str) -> bool;
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Algorithm<'a> {
-    pub name: &'a str,
-    pub bitsize: isize,
+/// Algorithm identifies a digest algorithm. Algorithms registered at runtime
+/// are represented by `Other` and resolved through the global registry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Algorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha512_256,
+    Sha3_256,
+    Sha3_512,
+    Blake3,
+    Other(String),
 }
 
-impl Algorithm<'_> {
-    pub fn new(name: &str, size: isize) -> Algorithm {
-        Algorithm {
-            name,
-            bitsize: size,
+impl Algorithm {
+    /// as_str returns the name of the algorithm as used in digests, e.g. `sha256`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Algorithm::Sha256 => SHA256,
+            Algorithm::Sha384 => SHA384,
+            Algorithm::Sha512 => SHA512,
+            Algorithm::Sha512_256 => SHA512_256,
+            Algorithm::Sha3_256 => SHA3_256,
+            Algorithm::Sha3_512 => SHA3_512,
+            Algorithm::Blake3 => BLAKE3,
+            Algorithm::Other(name) => name,
+        }
+    }
+
+    /// parallel_threshold returns the input size from which the algorithm
+    /// hashes with multiple threads by default, see
+    /// `Digester::with_parallel_threshold`. Only BLAKE3 does, and only when
+    /// the rayon feature is enabled.
+    pub fn parallel_threshold(&self) -> Option<usize> {
+        match self {
+            Algorithm::Blake3 if cfg!(feature = "rayon") => Some(DEFAULT_BLAKE3_PARALLEL_THRESHOLD),
+            _ => None,
         }
     }
+
+    // streaming_digester returns a Digester for the algorithm, failing for
+    // names which aren't registered with a hasher.
+    fn streaming_digester(&self) -> Result<Digester, Error> {
+        Digester::new(self.as_str())
+    }
 }
 
-impl CryptoHash for Algorithm<'static> {
-    fn available(self) -> bool {
-        true
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = Error;
 
-    fn string(self) -> &'static str {
-        self.name
+    /// from_str maps the built-in names to their variants. Any other name
+    /// following the algorithm grammar becomes `Other`, registered or not;
+    /// digesting with an unregistered one fails with `UnknownAlgorithm`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(match name {
+            SHA256 => Algorithm::Sha256,
+            SHA384 => Algorithm::Sha384,
+            SHA512 => Algorithm::Sha512,
+            SHA512_256 => Algorithm::Sha512_256,
+            SHA3_256 => Algorithm::Sha3_256,
+            SHA3_512 => Algorithm::Sha3_512,
+            BLAKE3 => Algorithm::Blake3,
+            _ => {
+                if !ALGORITHM_REGEX.is_match(name) {
+                    return Err(Error::InvalidInput(format!(
+                        "invalid digest algorithm {:?}",
+                        name
+                    )));
+                }
+                Algorithm::Other(name.to_string())
+            }
+        })
     }
+}
 
-    fn size(self) -> isize {
-        self.bitsize
+impl Serialize for Algorithm {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
     }
+}
 
-    fn set(self, name: &str) -> Result<Self, Error> {
-        match name {
-            SHA256 => Ok(Algorithm::new(SHA256, 256)),
-            SHA384 => Ok(Algorithm::new(SHA384, 384)),
-            SHA512 => Ok(Algorithm::new(SHA512, 512)),
-            _ => Err(Error::new(ErrorKind::Other, "Unsupported algorithm")),
-        }
+impl<'de> Deserialize<'de> for Algorithm {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
     }
+}
 
-    fn digester(&self) -> Box<dyn DynDigest> {
-        match self.name {
-            SHA256 => Box::new(Sha256::new()),
-            SHA384 => Box::new(Sha384::new()),
-            SHA512 => Box::new(Sha512::new()),
-            _ => panic!("Unsupported algorithm"),
+impl CryptoHash for Algorithm {
+    fn available(&self) -> bool {
+        match self {
+            Algorithm::Other(name) => algorithms().digester_factory(name).is_some(),
+            _ => true,
         }
     }
 
-    fn hash(self) -> Box<dyn DynDigest> {
-        self.digester()
+    fn string(&self) -> &str {
+        self.as_str()
     }
 
-    fn encode(&self, bytes: &[u8]) -> String {
-        let mut digest: Box<dyn DynDigest>;
-        match self.name {
-            SHA256 => {
-                digest = Box::new(Sha256::new());
-            }
-            SHA384 => {
-                digest = Box::new(Sha384::new());
+    fn size(&self) -> isize {
+        match self {
+            Algorithm::Sha256 | Algorithm::Sha512_256 | Algorithm::Sha3_256 | Algorithm::Blake3 => {
+                256
             }
-            SHA512 => {
-                digest = Box::new(Sha512::new());
-            }
-            BLAKE3 => {
-                let mut digest = blake3::Hasher::new();
-                digest.update(bytes);
-                return hex::encode(digest.finalize().as_bytes());
-            }
-            _ => panic!("Unsupported algorithm"),
-        };
-        digest.update(bytes);
-        hex::encode(digest.finalize())
+            Algorithm::Sha384 => 384,
+            Algorithm::Sha512 | Algorithm::Sha3_512 => 512,
+            Algorithm::Other(name) => algorithms().size(name).unwrap_or_default(),
+        }
     }
 
-    fn from_reader<R: std::io::Read>(&self, reader: R) -> String {
-        let mut digest: Box<dyn DynDigest>;
-        match self.name {
-            SHA256 => {
-                digest = Box::new(Sha256::new());
-            }
-            SHA384 => {
-                digest = Box::new(Sha384::new());
-            }
-            SHA512 => {
-                digest = Box::new(Sha512::new());
-            }
-            BLAKE3 => {
-                let mut digest = blake3::Hasher::new();
-                let mut reader = reader;
-                let mut buffer = [0; 1024];
-                loop {
-                    let len = match reader.read(&mut buffer) {
-                        Ok(len) => len,
-                        Err(_) => break,
-                    };
-                    if len == 0 {
-                        break;
-                    }
-                    digest.update(&buffer[..len]);
-                }
-                return hex::encode(digest.finalize().as_bytes());
-            }
-            _ => panic!("Unsupported algorithm"),
-        };
-        let mut reader = reader;
-        let mut buffer = [0; 1024];
-        loop {
-            let len = match reader.read(&mut buffer) {
-                Ok(len) => len,
-                Err(_) => break,
-            };
-            if len == 0 {
-                break;
-            }
-            digest.update(&buffer[..len]);
+    fn set(&self, name: &str) -> Result<Self, Error> {
+        match name.parse()? {
+            Algorithm::Other(name) => Err(DigestParseError::UnknownAlgorithm(name).into()),
+            algorithm => Ok(algorithm),
         }
-        hex::encode(digest.finalize())
     }
 
-    fn from_bytes(&self, bytes: &[u8]) -> String {
-        let mut digest: Box<dyn DynDigest>;
-        match self.name {
-            SHA256 => {
-                digest = Box::new(Sha256::new());
-            }
-            SHA384 => {
-                digest = Box::new(Sha384::new());
-            }
-            SHA512 => {
-                digest = Box::new(Sha512::new());
-            }
-            BLAKE3 => {
-                let mut digest = blake3::Hasher::new();
-                digest.update(bytes);
-                return hex::encode(digest.finalize().as_bytes());
-            }
-            _ => panic!("Unsupported algorithm"),
-        };
-        digest.update(bytes);
-        hex::encode(digest.finalize())
+    fn digester(&self) -> Result<Box<dyn DynDigest>, Error> {
+        Ok(match self {
+            Algorithm::Sha256 => Box::new(Sha256::new()),
+            Algorithm::Sha384 => Box::new(Sha384::new()),
+            Algorithm::Sha512 => Box::new(Sha512::new()),
+            Algorithm::Sha512_256 => Box::new(Sha512Trunc256::new()),
+            Algorithm::Sha3_256 => Box::new(Sha3_256::new()),
+            Algorithm::Sha3_512 => Box::new(Sha3_512::new()),
+            Algorithm::Blake3 => Box::new(Blake3Hasher(blake3::Hasher::new())),
+            Algorithm::Other(name) => match algorithms().digester_factory(name) {
+                Some(factory) => factory(),
+                None => return Err(DigestParseError::UnknownAlgorithm(name.clone()).into()),
+            },
+        })
     }
 
-    fn from_string(&self, str: &str) -> String {
-        let mut digest: Box<dyn DynDigest>;
-        match self.name {
-            SHA256 => {
-                digest = Box::new(Sha256::new());
-            }
-            SHA384 => {
-                digest = Box::new(Sha384::new());
-            }
-            SHA512 => {
-                digest = Box::new(Sha512::new());
-            }
-            BLAKE3 => {
-                let mut digest = blake3::Hasher::new();
-                digest.update(str.as_bytes());
-                return hex::encode(digest.finalize().as_bytes());
-            }
-            _ => panic!("Unsupported algorithm"),
-        };
-        digest.update(str.as_bytes());
-        hex::encode(digest.finalize())
+    fn hash(&self) -> Result<Box<dyn DynDigest>, Error> {
+        self.digester()
+    }
+
+    fn encode(&self, bytes: &[u8]) -> Result<String, Error> {
+        self.from_bytes(bytes)
+    }
+
+    fn from_reader<R: std::io::Read>(&self, reader: R) -> Result<String, Error> {
+        self.from_reader_with_buffer(reader, DEFAULT_BUFFER_SIZE)
+    }
+
+    fn from_reader_with_buffer<R: std::io::Read>(
+        &self,
+        reader: R,
+        buffer_size: usize,
+    ) -> Result<String, Error> {
+        let mut digester = self.streaming_digester()?;
+        digest_buffered(&mut digester, reader, buffer_size)?;
+        Ok(digester.finalize().encoded())
+    }
+
+    fn from_bytes(&self, bytes: &[u8]) -> Result<String, Error> {
+        let mut digester = self.streaming_digester()?;
+        digester.update(bytes);
+        Ok(digester.finalize().encoded())
+    }
+
+    fn from_string(&self, str: &str) -> Result<String, Error> {
+        self.from_bytes(str.as_bytes())
     }
 
     fn from_file(&self, path: &str) -> Result<String, Error> {
-        let mut digest: Box<dyn DynDigest>;
-        match self.name {
-            SHA256 => {
-                digest = Box::new(Sha256::new());
-            }
-            SHA384 => {
-                digest = Box::new(Sha384::new());
-            }
-            SHA512 => {
-                digest = Box::new(Sha512::new());
-            }
-            BLAKE3 => {
-                let mut digest = blake3::Hasher::new();
-                let mut file = std::fs::File::open(path)?;
-                let mut buffer = [0; 1024];
-                loop {
-                    let len = match std::io::Read::read(&mut file, &mut buffer) {
-                        Ok(len) => len,
-                        Err(_) => break,
-                    };
-                    if len == 0 {
-                        break;
-                    }
-                    digest.update(&buffer[..len]);
-                }
-                return Ok(hex::encode(digest.finalize().as_bytes()));
-            }
-            _ => panic!("Unsupported algorithm"),
-        };
-        let mut file = std::fs::File::open(path)?;
-        let mut buffer = [0; 1024];
-        loop {
-            let len = match std::io::Read::read(&mut file, &mut buffer) {
-                Ok(len) => len,
-                Err(_) => break,
-            };
-            if len == 0 {
-                break;
-            }
-            digest.update(&buffer[..len]);
-        }
-        Ok(hex::encode(digest.finalize()))
+        self.from_file_with_buffer(path, DEFAULT_BUFFER_SIZE)
+    }
+
+    fn from_file_with_buffer(&self, path: &str, buffer_size: usize) -> Result<String, Error> {
+        let mut digester = self.streaming_digester()?;
+        digest_buffered(&mut digester, std::fs::File::open(path)?, buffer_size)?;
+        Ok(digester.finalize().encoded())
     }
 
     fn validate(&self, str: &str) -> bool {
-        let re = regex::Regex::new(&format!(r"^[0-9a-f]{{{}}}$", self.bitsize / 4)).unwrap();
-        re.is_match(str)
+        if let Algorithm::Other(name) = self {
+            return algorithms().validate_encoded(name, str).unwrap_or_default();
+        }
+        check_hex_encoded(str, self.size()).is_ok()
+    }
+}
+
+// Blake3Hasher adapts blake3::Hasher to the digest traits, so that it can be
+// returned as a DynDigest.
+#[derive(Clone)]
+struct Blake3Hasher(blake3::Hasher);
+
+impl digest::Update for Blake3Hasher {
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.update(data.as_ref());
+    }
+}
+
+impl digest::FixedOutput for Blake3Hasher {
+    type OutputSize = U32;
+
+    fn finalize_into(self, out: &mut GenericArray<u8, U32>) {
+        out.copy_from_slice(self.0.finalize().as_bytes());
+    }
+
+    fn finalize_into_reset(&mut self, out: &mut GenericArray<u8, U32>) {
+        out.copy_from_slice(self.0.finalize().as_bytes());
+        self.0.reset();
+    }
+}
+
+impl digest::Reset for Blake3Hasher {
+    fn reset(&mut self) {
+        self.0.reset();
     }
 }
 
+// digest_buffered feeds reader to digester through a buffer of buffer_size bytes.
+pub(crate) fn digest_buffered<R: Read>(
+    digester: &mut Digester,
+    reader: R,
+    buffer_size: usize,
+) -> Result<(), Error> {
+    let buffer_size = digester.buffer_size(buffer_size.max(1));
+    let mut reader = BufReader::with_capacity(buffer_size, reader);
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        digester.update(buffer);
+        let len = buffer.len();
+        reader.consume(len);
+    }
+}
+
+// check_hex_encoded checks that encoded is the lowercase hex encoding of a
+// hash of size bits.
+fn check_hex_encoded(encoded: &str, size: isize) -> Result<(), DigestParseError> {
+    let expected = (size / 4) as usize;
+    if encoded.len() != expected {
+        return Err(DigestParseError::WrongLength {
+            expected,
+            actual: encoded.len(),
+        });
+    }
+    if !encoded
+        .bytes()
+        .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        return Err(DigestParseError::InvalidHex);
+    }
+    Ok(())
+}
+
 /// A digest is a cryptographic hash of a data stream.
-pub struct Algorithms<'a> {
-    algorithms: HashMap<&'a str, isize>,
+pub struct Algorithms {
+    algorithms: HashMap<String, Registration>,
 }
 
-impl<'a> Algorithms<'a> {
+/// DigesterFactory returns a new hasher for a custom algorithm.
+pub type DigesterFactory = Arc<dyn Fn() -> Box<dyn DynDigest> + Send + Sync>;
+
+/// EncodedValidator reports whether the encoded portion of a digest is valid
+/// for a custom algorithm.
+pub type EncodedValidator = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+struct Registration {
+    size: isize,
+    factory: Option<DigesterFactory>,
+    validator: Option<EncodedValidator>,
+}
+
+impl Algorithms {
     pub fn new() -> Self {
         let mut algs = Algorithms {
             algorithms: HashMap::new(),
         };
-        algs.register_algorithm(CANONICAL, 256);
-        algs.register_algorithm(SHA256, 256);
-        algs.register_algorithm(SHA384, 384);
-        algs.register_algorithm(SHA512, 512);
-        algs.register_algorithm(BLAKE3, 256);
+        for (name, size) in [
+            (SHA256, 256),
+            (SHA384, 384),
+            (SHA512, 512),
+            (BLAKE3, 256),
+            (SHA512_256, 256),
+            (SHA3_256, 256),
+            (SHA3_512, 512),
+        ] {
+            algs.algorithms.insert(
+                name.to_string(),
+                Registration {
+                    size,
+                    factory: None,
+                    validator: None,
+                },
+            );
+        }
         algs
     }
 
-    // Add an algorithm to the list of available algorithms.
-    pub fn register_algorithm(self: &mut Self, name: &'a str, size: isize) -> bool {
+    // Add an algorithm to the list of available algorithms. It returns false
+    // if the name is taken and fails if it doesn't follow the digest grammar.
+    pub fn register_algorithm(self: &mut Self, name: &str, size: isize) -> Result<bool, Error> {
+        self.register(
+            name,
+            Registration {
+                size,
+                factory: None,
+                validator: None,
+            },
+        )
+    }
+
+    /// register_algorithm_with adds an algorithm implemented outside of this
+    /// crate: `factory` returns a new hasher and `validator` checks the encoded
+    /// portion of digests. `Digester::new` and `CryptoHash` use the factories
+    /// of the global registry, `Digester::new_with` those of a given registry.
+    /// It returns false if the name is taken and fails if it doesn't follow the
+    /// digest grammar, e.g. `SHA-256`.
+    pub fn register_algorithm_with<F, V>(
+        &mut self,
+        name: &str,
+        size: isize,
+        factory: F,
+        validator: V,
+    ) -> Result<bool, Error>
+    where
+        F: Fn() -> Box<dyn DynDigest> + Send + Sync + 'static,
+        V: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.register(
+            name,
+            Registration {
+                size,
+                factory: Some(Arc::new(factory)),
+                validator: Some(Arc::new(validator)),
+            },
+        )
+    }
+
+    fn register(&mut self, name: &str, registration: Registration) -> Result<bool, Error> {
+        if !ALGORITHM_REGEX.is_match(name) {
+            return Err(Error::InvalidInput(format!(
+                "invalid digest algorithm {:?}",
+                name
+            )));
+        }
         match self.algorithms.get(name) {
-            Some(_) => false,
+            Some(_) => Ok(false),
             None => {
-                self.algorithms.insert(name, size);
-                true
+                self.algorithms.insert(name.to_string(), registration);
+                Ok(true)
             }
         }
     }
 
-    pub fn get_algorithm(self: &Self, name: &'a str) -> Option<Algorithm<'a>> {
-        match self.algorithms.get(name) {
-            Some(size) => Some(Algorithm::new(name, *size)),
-            None => None,
+    /// get_algorithm returns the algorithm registered under name.
+    pub fn get_algorithm(&self, name: &str) -> Option<Algorithm> {
+        self.algorithms.get(name)?;
+        name.parse().ok()
+    }
+
+    /// size returns the size in bits of the algorithm registered under name.
+    pub fn size(&self, name: &str) -> Option<isize> {
+        self.algorithms
+            .get(name)
+            .map(|registration| registration.size)
+    }
+
+    /// digester_factory returns the hasher factory registered for a custom algorithm.
+    pub fn digester_factory(&self, name: &str) -> Option<DigesterFactory> {
+        self.algorithms.get(name)?.factory.clone()
+    }
+
+    /// validate_encoded reports whether encoded is a valid encoded portion for
+    /// the algorithm, see check_encoded. It returns None if the algorithm isn't
+    /// registered.
+    pub fn validate_encoded(&self, name: &str, encoded: &str) -> Option<bool> {
+        match self.check_encoded(name, encoded) {
+            Ok(()) => Some(true),
+            Err(DigestParseError::UnknownAlgorithm(_)) => None,
+            Err(_) => Some(false),
+        }
+    }
+
+    /// check_encoded checks the encoded portion of a digest of the algorithm:
+    /// the registered validator decides for custom algorithms, others require
+    /// lowercase hex of the algorithm size.
+    pub fn check_encoded(&self, name: &str, encoded: &str) -> Result<(), DigestParseError> {
+        let registration = self
+            .algorithms
+            .get(name)
+            .ok_or_else(|| DigestParseError::UnknownAlgorithm(name.to_string()))?;
+        match &registration.validator {
+            Some(validator) if validator(encoded) => Ok(()),
+            Some(_) => Err(DigestParseError::InvalidEncoded),
+            None => check_hex_encoded(encoded, registration.size),
         }
     }
 }
 
+static GLOBAL_ALGORITHMS: OnceCell<RwLock<Algorithms>> = OnceCell::new();
+
+impl Algorithms {
+    /// global returns the process-wide algorithm registry, initialized with the
+    /// built-in algorithms on first use. Algorithms registered here are visible
+    /// to `Digest::validate` and every other lookup in the crate.
+    pub fn global() -> &'static RwLock<Algorithms> {
+        GLOBAL_ALGORITHMS.get_or_init(|| RwLock::new(Algorithms::new()))
+    }
+}
+
+/// algorithms returns the global algorithm registry for lookups. The guard
+/// blocks registration while held, so don't keep it around. A registry
+/// poisoned by a panicking thread is still returned, since registration
+/// never leaves it half updated.
+pub fn algorithms() -> RwLockReadGuard<'static, Algorithms> {
+    Algorithms::global()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+fn algorithms_mut() -> RwLockWriteGuard<'static, Algorithms> {
+    Algorithms::global()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// register_algorithm adds an algorithm to the global registry, see
+/// `Algorithms::register_algorithm`. It returns false if the name is taken.
+pub fn register_algorithm(name: &str, size: isize) -> Result<bool, Error> {
+    algorithms_mut().register_algorithm(name, size)
+}
+
+/// register_algorithm_with adds an algorithm with its own hasher to the global
+/// registry, see `Algorithms::register_algorithm_with`. It returns false if the
+/// name is taken.
+pub fn register_algorithm_with<F, V>(
+    name: &str,
+    size: isize,
+    factory: F,
+    validator: V,
+) -> Result<bool, Error>
+where
+    F: Fn() -> Box<dyn DynDigest> + Send + Sync + 'static,
+    V: Fn(&str) -> bool + Send + Sync + 'static,
+{
+    algorithms_mut().register_algorithm_with(name, size, factory, validator)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Algorithms, CryptoHash};
+    use super::{Algorithm, Algorithms, CryptoHash};
 
     #[test]
     fn encode_canonical() {
         let algs = Algorithms::new();
         let alg = algs.get_algorithm(super::CANONICAL).unwrap();
         assert_eq!(
-            alg.encode(b"hello"),
+            alg.encode(b"hello").unwrap(),
             "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
         );
         assert_eq!(
-            alg.encode(b"hello"),
+            alg.encode(b"hello").unwrap(),
             "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
         );
     }
@@ -328,11 +568,11 @@ mod tests {
         let algs = Algorithms::new();
         let alg = algs.get_algorithm(super::SHA256).unwrap();
         assert_eq!(
-            alg.encode(b"hello"),
+            alg.encode(b"hello").unwrap(),
             "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
         );
         assert_eq!(
-            alg.encode(b"hello"),
+            alg.encode(b"hello").unwrap(),
             "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
         );
     }
@@ -342,27 +582,89 @@ mod tests {
         let algs = Algorithms::new();
         let alg = algs.get_algorithm(super::BLAKE3).unwrap();
         assert_eq!(
-            alg.encode(b"hello"),
+            alg.encode(b"hello").unwrap(),
             "ea8f163db38682925e4491c5e58d4bb3506ef8c14eb78a86e908c5624a67200f"
         );
     }
 
+    #[test]
+    fn encode_sha3() {
+        let algs = Algorithms::new();
+        let alg = algs.get_algorithm(super::SHA3_256).unwrap();
+        assert_eq!(
+            alg.encode(b"hello").unwrap(),
+            "3338be694f50c5f338814986cdf0686453a888b84f424d792af4b9202398f392"
+        );
+        assert!(alg.validate(&alg.encode(b"hello").unwrap()));
+        let alg = algs.get_algorithm(super::SHA3_512).unwrap();
+        assert_eq!(alg.encode(b"hello").unwrap().len(), 128);
+        assert!(alg.validate(&alg.encode(b"hello").unwrap()));
+    }
+
+    #[test]
+    fn encode_sha512_256() {
+        let algs = Algorithms::new();
+        let alg = algs.get_algorithm(super::SHA512_256).unwrap();
+        assert_eq!(
+            alg.encode(b"hello").unwrap(),
+            "e30d87cfa2a75db545eac4d61baf970366a8357c7f72fa95b52d0accb698f13a"
+        );
+        assert!(alg.validate(&alg.encode(b"hello").unwrap()));
+    }
+
     #[test]
     fn from_reader() {
         let algs = Algorithms::new();
         let alg = algs.get_algorithm(super::SHA256).unwrap();
         assert_eq!(
-            alg.from_reader(b"hello".as_ref()),
+            alg.from_reader(b"hello".as_ref()).unwrap(),
             "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
         );
     }
 
+    #[test]
+    fn from_reader_error() {
+        // FailingReader returns some content, then fails.
+        struct FailingReader(bool);
+        impl std::io::Read for FailingReader {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if std::mem::replace(&mut self.0, true) {
+                    return Err(std::io::Error::other("connection reset"));
+                }
+                buf[0] = b'h';
+                Ok(1)
+            }
+        }
+        let alg = Algorithms::new().get_algorithm(super::SHA256).unwrap();
+        let err = alg.from_reader(FailingReader(false)).unwrap_err();
+        assert!(matches!(err, super::Error::Io(_)));
+        assert!(alg
+            .from_reader_with_buffer(FailingReader(false), 1)
+            .is_err());
+    }
+
+    #[test]
+    fn from_reader_with_buffer() {
+        let algs = Algorithms::new();
+        let content = vec![7u8; 100_000];
+        for name in [super::SHA256, super::BLAKE3] {
+            let alg = algs.get_algorithm(name).unwrap();
+            for buffer_size in [0, 1, 1000, super::DEFAULT_BUFFER_SIZE] {
+                assert_eq!(
+                    alg.from_reader_with_buffer(content.as_slice(), buffer_size)
+                        .unwrap(),
+                    alg.from_bytes(&content).unwrap()
+                );
+            }
+        }
+    }
+
     #[test]
     fn from_bytes() {
         let algs = Algorithms::new();
         let alg = algs.get_algorithm(super::SHA256).unwrap();
         assert_eq!(
-            alg.from_bytes(b"hello"),
+            alg.from_bytes(b"hello").unwrap(),
             "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
         );
     }
@@ -381,9 +683,144 @@ mod tests {
     fn validate() {
         let algs = Algorithms::new();
         let alg = algs.get_algorithm(super::SHA256).unwrap();
+        assert!(alg.validate("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"));
+    }
+
+    #[test]
+    fn global_registration() {
+        assert!(Algorithms::global()
+            .write()
+            .unwrap()
+            .register_algorithm("test-global", 128)
+            .unwrap());
+        let alg = Algorithms::global()
+            .read()
+            .unwrap()
+            .get_algorithm("test-global")
+            .unwrap();
+        assert_eq!(alg, Algorithm::Other("test-global".to_string()));
+        assert_eq!(alg.size(), 128);
+        assert!(super::algorithms()
+            .get_algorithm(&String::from(super::SHA256))
+            .is_some());
+    }
+
+    #[test]
+    fn concurrent_registration() {
+        let registered = (0..8)
+            .map(|_| std::thread::spawn(|| super::register_algorithm("test-concurrent", 64)))
+            .map(|handle| handle.join().unwrap().unwrap())
+            .filter(|registered| *registered)
+            .count();
+        assert_eq!(registered, 1);
+        let alg = super::algorithms()
+            .get_algorithm("test-concurrent")
+            .unwrap();
+        assert_eq!(alg.size(), 64);
+    }
+
+    #[test]
+    fn register_algorithm_with() {
+        use sha2::Digest;
+        let mut algs = Algorithms::new();
+        assert!(algs
+            .register_algorithm_with(
+                "test-sha224",
+                224,
+                || Box::new(sha2::Sha224::new()),
+                |encoded| encoded.len() == 56,
+            )
+            .unwrap());
+        assert!(!algs
+            .register_algorithm_with(
+                super::SHA256,
+                256,
+                || Box::new(sha2::Sha256::new()),
+                |_| true,
+            )
+            .unwrap());
+        for invalid in ["SHA-224", "sha224:x", "sha224-", ""] {
+            assert!(algs
+                .register_algorithm_with(invalid, 224, || Box::new(sha2::Sha224::new()), |_| true,)
+                .is_err());
+            assert!(algs.register_algorithm(invalid, 224).is_err());
+        }
+        assert!(algs.register_algorithm("test.sha/224+x_y", 224).unwrap());
+        assert!(algs.digester_factory("test-sha224").is_some());
+        assert!(algs.digester_factory(super::SHA256).is_none());
         assert_eq!(
-            alg.validate("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"),
-            true
+            algs.validate_encoded("test-sha224", &"A".repeat(56)),
+            Some(true)
+        );
+        assert_eq!(
+            algs.validate_encoded(super::SHA256, &"A".repeat(64)),
+            Some(false)
+        );
+        assert_eq!(algs.validate_encoded("md5", ""), None);
+    }
+
+    #[test]
+    fn unregistered_algorithm() {
+        let alg: Algorithm = "test-unregistered".parse().unwrap();
+        assert!(!alg.available());
+        let unknown = |err: super::Error| {
+            matches!(
+                err,
+                super::Error::Digest(super::DigestParseError::UnknownAlgorithm(_))
+            )
+        };
+        assert!(unknown(alg.from_bytes(b"hello").unwrap_err()));
+        assert!(unknown(alg.from_reader(b"hello".as_ref()).unwrap_err()));
+        assert!(unknown(alg.from_file("Cargo.toml").unwrap_err()));
+        assert!(alg.digester().is_err());
+    }
+
+    #[test]
+    fn blake3_digester() {
+        let mut hasher = Algorithm::Blake3.hash().unwrap();
+        hasher.update(b"hello");
+        assert_eq!(
+            hex::encode(hasher.finalize()),
+            Algorithm::Blake3.from_bytes(b"hello").unwrap()
+        );
+    }
+
+    #[test]
+    fn dyn_crypto_hash() {
+        let algs: Vec<Box<dyn CryptoHash>> =
+            vec![Box::new(Algorithm::Sha256), Box::new(Algorithm::Blake3)];
+        for alg in algs {
+            assert!(alg.available());
+            assert!(alg.validate(&alg.encode(b"hello").unwrap()));
+        }
+    }
+
+    #[test]
+    fn parse_algorithm() {
+        for name in [
+            super::SHA256,
+            super::SHA512_256,
+            super::SHA3_512,
+            super::BLAKE3,
+        ] {
+            let alg: Algorithm = name.parse().unwrap();
+            assert_eq!(alg.to_string(), name);
+            assert!(!matches!(alg, Algorithm::Other(_)));
+        }
+        assert_eq!(
+            "x-custom".parse::<Algorithm>().unwrap(),
+            Algorithm::Other("x-custom".to_string())
+        );
+        assert!("SHA256".parse::<Algorithm>().is_err());
+        assert!(Algorithm::Sha256.set("md5").is_err());
+        assert_eq!(
+            Algorithm::Sha256.set(super::SHA384).unwrap(),
+            Algorithm::Sha384
+        );
+        assert_eq!(Algorithm::Sha384.size(), 384);
+        assert_eq!(
+            serde_json::to_string(&Algorithm::Sha512).unwrap(),
+            "\"sha512\""
         );
     }
 
@@ -391,9 +828,6 @@ mod tests {
     fn validate_blake3() {
         let algs = Algorithms::new();
         let alg = algs.get_algorithm(super::BLAKE3).unwrap();
-        assert_eq!(
-            alg.validate("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"),
-            true
-        );
+        assert!(alg.validate("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"));
     }
 }
@@ -0,0 +1,123 @@
+use super::digest::Digest;
+use super::digester::Digester;
+use crate::error::Error;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// from_async_reader digests the content of reader with the named algorithm.
+pub async fn from_async_reader<R>(algorithm: &str, mut reader: R) -> Result<Digest, Error>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut digester = Digester::new(algorithm)?;
+    tokio::io::copy(&mut reader, &mut digester).await?;
+    Ok(digester.finalize())
+}
+
+impl AsyncWrite for Digester {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        self.get_mut().update(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Result<(), std::io::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// AsyncDigestWriter forwards writes to an inner async writer while hashing
+/// the written bytes, the async counterpart of TeeDigestWriter.
+pub struct AsyncDigestWriter<W: AsyncWrite + Unpin> {
+    writer: W,
+    digester: Digester,
+    size: u64,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncDigestWriter<W> {
+    /// new returns a writer forwarding to writer and hashing with the named algorithm.
+    pub fn new(writer: W, algorithm: &str) -> Result<Self, Error> {
+        Ok(AsyncDigestWriter {
+            writer,
+            digester: Digester::new(algorithm)?,
+            size: 0,
+        })
+    }
+
+    /// size returns the number of bytes written to the inner writer.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// digest returns the digest of the bytes written to the inner writer.
+    pub fn digest(&self) -> Digest {
+        self.digester.digest()
+    }
+
+    /// into_inner returns the inner writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncDigestWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.writer).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.digester.update(&buf[..n]);
+            this.size += n as u64;
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+        Pin::new(&mut self.get_mut().writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), std::io::Error>> {
+        Pin::new(&mut self.get_mut().writer).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::algorithm::SHA256;
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    const HELLO: &str = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[tokio::test]
+    async fn test_from_async_reader() {
+        let digest = from_async_reader(SHA256, b"hello".as_ref()).await.unwrap();
+        assert_eq!(digest.to_string(), HELLO);
+    }
+
+    #[tokio::test]
+    async fn test_async_digest_writer() {
+        let mut writer = AsyncDigestWriter::new(Vec::new(), SHA256).unwrap();
+        writer.write_all(b"hello").await.unwrap();
+        assert_eq!(writer.size(), 5);
+        assert_eq!(writer.digest().to_string(), HELLO);
+        assert_eq!(writer.into_inner(), b"hello");
+    }
+}
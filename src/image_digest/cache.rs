@@ -0,0 +1,197 @@
+use super::digest::Digest;
+use crate::clock::{Clock, SystemClock};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+struct Entry<V> {
+    value: V,
+    inserted: DateTime<Utc>,
+    last_used: u64,
+}
+
+/// DigestCache memoizes values derived from content, such as parsed manifests
+/// or configs, keyed by the digest of that content. Entries expire after an
+/// optional time to live, and the least recently used entry is evicted when
+/// the cache is full. A cache always holds at least one entry.
+pub struct DigestCache<V> {
+    entries: HashMap<Digest, Entry<V>>,
+    capacity: usize,
+    ttl: Option<Duration>,
+    clock: Arc<dyn Clock + Send + Sync>,
+    tick: u64,
+}
+
+impl<V> DigestCache<V> {
+    /// new returns a cache holding at most `capacity` entries, which never expire.
+    pub fn new(capacity: usize) -> Self {
+        DigestCache {
+            entries: HashMap::new(),
+            capacity: capacity.max(1),
+            ttl: None,
+            clock: Arc::new(SystemClock),
+            tick: 0,
+        }
+    }
+
+    /// with_ttl returns a cache holding at most `capacity` entries, each
+    /// expiring `ttl` after it was inserted.
+    pub fn with_ttl(capacity: usize, ttl: Duration) -> Self {
+        DigestCache {
+            ttl: Some(ttl),
+            ..Self::new(capacity)
+        }
+    }
+
+    /// with_clock makes the cache tell the age of entries with clock instead
+    /// of the system time.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock + Send + Sync>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// get returns the value cached for digest, if present and not expired.
+    pub fn get(&mut self, digest: &Digest) -> Option<&V> {
+        self.expire(digest);
+        let entry = self.entries.get_mut(digest)?;
+        self.tick += 1;
+        entry.last_used = self.tick;
+        Some(&entry.value)
+    }
+
+    /// insert caches value for digest, returning the previously cached value.
+    pub fn insert(&mut self, digest: &Digest, value: V) -> Option<V> {
+        if !self.entries.contains_key(digest) && self.entries.len() >= self.capacity {
+            self.evict();
+        }
+        self.tick += 1;
+        self.entries
+            .insert(
+                digest.clone(),
+                Entry {
+                    value,
+                    inserted: self.clock.now(),
+                    last_used: self.tick,
+                },
+            )
+            .map(|entry| entry.value)
+    }
+
+    /// get_or_try_insert_with returns the value cached for digest, computing
+    /// and caching it with `f` on a miss.
+    pub fn get_or_try_insert_with<E, F>(&mut self, digest: &Digest, f: F) -> Result<&V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        if self.get(digest).is_none() {
+            self.insert(digest, f()?);
+        }
+        Ok(&self.entries[digest].value)
+    }
+
+    /// remove drops the value cached for digest.
+    pub fn remove(&mut self, digest: &Digest) -> Option<V> {
+        self.entries.remove(digest).map(|entry| entry.value)
+    }
+
+    /// len returns the number of cached entries, including expired ones not yet dropped.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// is_empty reports whether the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// clear drops all cached values.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn expire(&mut self, key: &Digest) {
+        if let (Some(ttl), Some(entry)) = (self.ttl, self.entries.get(key)) {
+            if expired(entry, self.clock.now(), ttl) {
+                self.entries.remove(key);
+            }
+        }
+    }
+
+    fn evict(&mut self) {
+        if let Some(ttl) = self.ttl {
+            let now = self.clock.now();
+            self.entries.retain(|_, entry| !expired(entry, now, ttl));
+        }
+        if self.entries.len() < self.capacity {
+            return;
+        }
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+// expired reports whether entry is at least ttl old at now. Entries inserted
+// after now, when the clock went back, aren't.
+fn expired<V>(entry: &Entry<V>, now: DateTime<Utc>, ttl: Duration) -> bool {
+    (now - entry.inserted).to_std().is_ok_and(|age| age >= ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    fn digest(encoded: &str) -> Digest {
+        Digest {
+            name: "sha256".to_string(),
+            digest: format!("sha256:{}", encoded),
+        }
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut cache = DigestCache::new(2);
+        cache.insert(&digest("a"), 1);
+        cache.insert(&digest("b"), 2);
+        assert_eq!(cache.get(&digest("a")), Some(&1));
+        cache.insert(&digest("c"), 3);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&digest("b")), None);
+        assert_eq!(cache.get(&digest("a")), Some(&1));
+    }
+
+    #[test]
+    fn test_ttl() {
+        let mut cache = DigestCache::with_ttl(2, Duration::ZERO);
+        cache.insert(&digest("a"), 1);
+        assert_eq!(cache.get(&digest("a")), None);
+
+        let clock = Arc::new(FixedClock::new(Utc::now()));
+        let mut cache = DigestCache::with_ttl(2, Duration::from_secs(60)).with_clock(clock.clone());
+        cache.insert(&digest("a"), 1);
+        clock.advance(chrono::Duration::seconds(30));
+        cache.insert(&digest("b"), 2);
+        assert_eq!(cache.get(&digest("a")), Some(&1));
+        clock.advance(chrono::Duration::seconds(30));
+        assert_eq!(cache.get(&digest("a")), None);
+        assert_eq!(cache.get(&digest("b")), Some(&2));
+        cache.insert(&digest("c"), 3);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_get_or_try_insert_with() {
+        let mut cache = DigestCache::new(1);
+        let value: Result<_, ()> = cache.get_or_try_insert_with(&digest("a"), || Ok(1));
+        assert_eq!(value, Ok(&1));
+        let value: Result<_, ()> = cache.get_or_try_insert_with(&digest("a"), || Ok(2));
+        assert_eq!(value, Ok(&1));
+    }
+}
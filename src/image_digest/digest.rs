@@ -1,7 +1,38 @@
-use serde::{Deserialize, Serialize};
+use crate::error::Error;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::string::String;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+// DIGEST_REGEX matches the digest grammar, with `/` allowed as algorithm separator.
+static DIGEST_REGEX: Lazy<regex::Regex> =
+    Lazy::new(|| regex::Regex::new(r"^[a-z0-9]+(?:[./+_-][a-z0-9]+)*:[a-zA-Z0-9=_-]+$").unwrap());
+
+/// DigestParseError tells why a digest failed to parse or validate.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DigestParseError {
+    /// The digest has no `:` between algorithm and encoded portion.
+    #[error("invalid checksum digest: missing ':' separator")]
+    MissingSeparator,
+    /// The digest doesn't follow the `algorithm:encoded` grammar.
+    #[error("invalid checksum digest format")]
+    InvalidFormat,
+    /// The algorithm isn't registered.
+    #[error("unsupported digest algorithm {0:?}")]
+    UnknownAlgorithm(String),
+    /// The encoded portion isn't lowercase hex.
+    #[error("invalid checksum digest: encoded portion must be lowercase hex")]
+    InvalidHex,
+    /// The encoded portion doesn't have the length of the algorithm's hash.
+    #[error("invalid checksum digest length: expected {expected} characters, got {actual}")]
+    WrongLength { expected: usize, actual: usize },
+    /// The encoded portion was rejected by the validator of a custom algorithm.
+    #[error("invalid checksum digest encoding")]
+    InvalidEncoded,
+}
+
+/// Digest serializes as its canonical `algorithm:encoded` string. Digests are
+/// ordered by algorithm, then by encoded value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Digest {
     pub name: String,
     pub digest: String,
@@ -9,13 +40,13 @@ pub struct Digest {
 
 impl Digest {
     pub fn new(alg: super::algorithm::Algorithm, digest: &str) -> Self {
-        let name = alg.name.to_string();
+        let name = alg.to_string();
         let digest = format!("{}:{}", name, digest.to_string());
         Self { name, digest }
     }
 
     pub fn new_from_bytes(alg: super::algorithm::Algorithm, bytes: &[u8]) -> Self {
-        let name = alg.name.to_string();
+        let name = alg.to_string();
         let digest = format!("{}:{}", name, String::from_utf8(bytes.to_vec()).unwrap());
         Self { name, digest }
     }
@@ -36,29 +67,119 @@ impl Digest {
         self.digest.find(':').unwrap()
     }
 
-    pub fn validate(self: &Self) -> Result<(), std::io::Error> {
-        let alg = self.algorithm();
-        match alg.as_str() {
-            super::algorithm::SHA256 | super::algorithm::SHA384 | super::algorithm::SHA512 => {}
-            _ => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "invalid checksum digest algorithm",
-                ));
-            }
+    /// validate checks the digest against the global algorithm registry, see validate_with.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.validate_with(&super::algorithm::algorithms())
+    }
+
+    /// validate_with checks that the digest follows the digest grammar, extended
+    /// with `/` as algorithm separator for names such as `sha512/256`, that its
+    /// algorithm is registered in `algorithms` and that the encoded portion is
+    /// valid for that algorithm: lowercase hex of the algorithm's hash length,
+    /// or accepted by the validator of a custom algorithm. Failures are
+    /// reported as `Error::Digest`.
+    pub fn validate_with(&self, algorithms: &super::algorithm::Algorithms) -> Result<(), Error> {
+        if !self.digest.contains(':') {
+            return Err(DigestParseError::MissingSeparator.into());
+        }
+        if !DIGEST_REGEX.is_match(&self.digest) {
+            return Err(DigestParseError::InvalidFormat.into());
         }
-        let re = regex::Regex::new(r"^[a-z0-9]+(?:[.+_-][a-z0-9]+)*:[a-zA-Z0-9=_-]+$").unwrap();
-        if re.is_match(&self.digest) {
-            Ok(())
+        let (algorithm, encoded) = self.parts();
+        Ok(algorithms.check_encoded(algorithm, encoded)?)
+    }
+}
+
+impl Digest {
+    // parts splits the digest into its algorithm and encoded portions.
+    pub(crate) fn parts(&self) -> (&str, &str) {
+        self.digest.split_once(':').unwrap_or((&self.digest, ""))
+    }
+}
+
+impl PartialOrd for Digest {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Digest {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.parts()
+            .cmp(&other.parts())
+            .then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+/// DEFAULT_SHORT_LENGTH is the number of encoded characters kept by the
+/// alternate format of Digest, `{:#}`.
+pub const DEFAULT_SHORT_LENGTH: usize = 12;
+
+impl Digest {
+    /// short returns the digest with its encoded portion truncated to len
+    /// characters and followed by `…`, e.g. `sha256:2cf24dba…`, for logs and
+    /// command line output. It is meant for display only: serialization and
+    /// to_string always use the complete digest.
+    pub fn short(&self, len: usize) -> String {
+        let (algorithm, encoded) = self.parts();
+        match encoded.get(..len) {
+            Some(prefix) if prefix.len() < encoded.len() => format!("{}:{}…", algorithm, prefix),
+            _ => self.digest.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Digest {
+    /// fmt writes the complete digest, or with the alternate flag `{:#}` the
+    /// digest shortened to DEFAULT_SHORT_LENGTH characters, see short.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            f.write_str(&self.short(DEFAULT_SHORT_LENGTH))
         } else {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "invalid checksum digest format",
-            ))
+            f.write_str(&self.digest)
         }
     }
 }
 
+impl std::str::FromStr for Digest {
+    type Err = Error;
+
+    /// from_str parses and validates a digest in the `algorithm:encoded` form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = match s.find(':') {
+            Some(index) => s[..index].to_string(),
+            None => return Err(DigestParseError::MissingSeparator.into()),
+        };
+        let digest = Digest {
+            name,
+            digest: s.to_string(),
+        };
+        digest.validate()?;
+        Ok(digest)
+    }
+}
+
+impl Serialize for Digest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.digest)
+    }
+}
+
+impl<'de> Deserialize<'de> for Digest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl TryFrom<&str> for Digest {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,8 +188,129 @@ mod tests {
     fn test_validate() {
         let d = Digest {
             name: "sha256".to_string(),
-            digest: "sha256:abcdefghijklmnopqrstuvwxyz0123456789".to_string(),
+            digest: "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+                .to_string(),
         };
         assert!(d.validate().is_ok());
     }
+
+    #[test]
+    fn test_validate_registry() {
+        let blake3: Digest =
+            "blake3:ea8f163db38682925e4491c5e58d4bb3506ef8c14eb78a86e908c5624a67200f"
+                .parse()
+                .unwrap();
+        assert!(blake3.validate().is_ok());
+
+        let short = Digest {
+            name: "sha256".to_string(),
+            digest: "sha256:abcdefghijklmnopqrstuvwxyz0123456789".to_string(),
+        };
+        assert!(matches!(
+            short.validate().unwrap_err(),
+            Error::Digest(DigestParseError::WrongLength { .. })
+        ));
+        let digest_error = |digest: &str| {
+            let digest = Digest {
+                name: "sha256".to_string(),
+                digest: digest.to_string(),
+            };
+            match digest.validate() {
+                Err(Error::Digest(err)) => Some(err),
+                _ => None,
+            }
+        };
+        assert_eq!(
+            digest_error("sha256:abcd"),
+            Some(DigestParseError::WrongLength {
+                expected: 64,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            digest_error(&format!("sha256:{}", "A".repeat(64))),
+            Some(DigestParseError::InvalidHex)
+        );
+        assert_eq!(
+            digest_error("sha256"),
+            Some(DigestParseError::MissingSeparator)
+        );
+        assert_eq!(
+            digest_error("sha256:abc+def"),
+            Some(DigestParseError::InvalidFormat)
+        );
+        assert_eq!(
+            digest_error("md5:abcd"),
+            Some(DigestParseError::UnknownAlgorithm("md5".to_string()))
+        );
+
+        let unknown = Digest {
+            name: "md5".to_string(),
+            digest: "md5:5d41402abc4b2a76b9719d911017c592".to_string(),
+        };
+        assert!(matches!(
+            unknown.validate().unwrap_err(),
+            Error::Digest(DigestParseError::UnknownAlgorithm(_))
+        ));
+
+        let truncated: Digest =
+            "sha512/256:e30d87cfa2a75db545eac4d61baf970366a8357c7f72fa95b52d0accb698f13a"
+                .parse()
+                .unwrap();
+        assert_eq!(truncated.algorithm(), "sha512/256");
+        assert!("sha512/256:e30d87cf".parse::<Digest>().is_err());
+
+        let mut algorithms = crate::image_digest::algorithm::Algorithms::new();
+        algorithms.register_algorithm("md5", 128).unwrap();
+        assert!(unknown.validate_with(&algorithms).is_ok());
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let s = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let d: Digest = s.parse().unwrap();
+        assert_eq!(d.name, "sha256");
+        assert_eq!(d.to_string(), s);
+        assert_eq!(Digest::try_from(s).unwrap(), d);
+        assert!("2cf24dba".parse::<Digest>().is_err());
+        assert!("md5:2cf24dba".parse::<Digest>().is_err());
+        assert!("sha256:".parse::<Digest>().is_err());
+    }
+
+    #[test]
+    fn test_short() {
+        let s = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let d: Digest = s.parse().unwrap();
+        assert_eq!(d.short(8), "sha256:2cf24dba…");
+        assert_eq!(d.short(64), s);
+        assert_eq!(d.short(100), s);
+        assert_eq!(format!("{:#}", d), "sha256:2cf24dba5fb0…");
+        assert_eq!(format!("{}", d), s);
+        assert_eq!(serde_json::to_string(&d).unwrap(), format!("\"{}\"", s));
+    }
+
+    #[test]
+    fn test_serde() {
+        let s = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let d: Digest = s.parse().unwrap();
+        let json = serde_json::to_string(&d).unwrap();
+        assert_eq!(json, format!("\"{}\"", s));
+        assert_eq!(serde_json::from_str::<Digest>(&json).unwrap(), d);
+        assert!(serde_json::from_str::<Digest>("\"not a digest\"").is_err());
+    }
+
+    #[test]
+    fn test_ordering() {
+        let a: Digest = format!("sha256:{}", "a".repeat(64)).parse().unwrap();
+        let b: Digest = format!("sha256:{}", "b".repeat(64)).parse().unwrap();
+        let c: Digest = format!("sha512:{}", "0".repeat(128)).parse().unwrap();
+        let mut digests = vec![c.clone(), b.clone(), a.clone()];
+        digests.sort();
+        assert_eq!(digests, vec![a.clone(), b.clone(), c]);
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(a.clone());
+        assert!(set.contains(&a));
+        assert!(!set.contains(&b));
+    }
 }
@@ -0,0 +1,318 @@
+#[cfg(feature = "rayon")]
+use super::algorithm::Algorithm;
+use super::algorithm::{
+    algorithms, digest_buffered, Algorithms, BLAKE3, DEFAULT_BUFFER_SIZE, SHA256, SHA384, SHA3_256,
+    SHA3_512, SHA512, SHA512_256,
+};
+use super::digest::{Digest, DigestParseError};
+use crate::error::Error;
+use crate::specs::v1::descriptor::Descriptor;
+use digest::DynDigest;
+use sha2::{Sha256, Sha384, Sha512, Sha512Trunc256};
+use sha3::{Sha3_256, Sha3_512};
+use std::io::{Read, Write};
+
+// MAX_PARALLEL_BUFFER_SIZE bounds the buffer grown to reach the parallel threshold.
+#[cfg(feature = "rayon")]
+const MAX_PARALLEL_BUFFER_SIZE: usize = 16 * 1024 * 1024;
+
+enum Hasher {
+    Dyn(Box<dyn DynDigest>),
+    Blake3(Box<blake3::Hasher>),
+}
+
+/// Digester computes a Digest incrementally from the bytes written to it, so
+/// it can be used as the destination of `std::io::copy`.
+pub struct Digester {
+    name: String,
+    hasher: Hasher,
+    #[cfg(feature = "rayon")]
+    parallel_threshold: Option<usize>,
+    #[cfg(all(test, feature = "rayon"))]
+    parallel_updates: usize,
+}
+
+impl Digester {
+    /// new returns a digester for the named algorithm, e.g. `sha256`. Custom
+    /// algorithms are looked up in the global registry.
+    pub fn new(algorithm: &str) -> Result<Self, Error> {
+        Self::new_with(algorithm, &algorithms())
+    }
+
+    /// new_with returns a digester for the named algorithm, looking custom
+    /// algorithms up in `algorithms` instead of the global registry.
+    pub fn new_with(algorithm: &str, algorithms: &Algorithms) -> Result<Self, Error> {
+        let hasher = match algorithm {
+            SHA256 => Hasher::Dyn(Box::new(Sha256::default())),
+            SHA384 => Hasher::Dyn(Box::new(Sha384::default())),
+            SHA512 => Hasher::Dyn(Box::new(Sha512::default())),
+            SHA512_256 => Hasher::Dyn(Box::new(Sha512Trunc256::default())),
+            SHA3_256 => Hasher::Dyn(Box::new(Sha3_256::default())),
+            SHA3_512 => Hasher::Dyn(Box::new(Sha3_512::default())),
+            BLAKE3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+            _ => match algorithms.digester_factory(algorithm) {
+                Some(factory) => Hasher::Dyn(factory()),
+                None => {
+                    return Err(DigestParseError::UnknownAlgorithm(algorithm.to_string()).into())
+                }
+            },
+        };
+        Ok(Digester {
+            name: algorithm.to_string(),
+            hasher,
+            #[cfg(feature = "rayon")]
+            parallel_threshold: algorithm
+                .parse::<Algorithm>()
+                .ok()
+                .and_then(|algorithm| algorithm.parallel_threshold()),
+            #[cfg(all(test, feature = "rayon"))]
+            parallel_updates: 0,
+        })
+    }
+
+    /// with_parallel_threshold makes the digester hash inputs of at least
+    /// `threshold` bytes with multiple threads, using the rayon thread pool,
+    /// instead of the default of the algorithm. `None` disables multithreaded
+    /// hashing. Only BLAKE3 supports it, other algorithms ignore the threshold.
+    #[cfg(feature = "rayon")]
+    pub fn with_parallel_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.parallel_threshold = threshold;
+        self
+    }
+
+    /// parallel_threshold returns the input size from which the digester
+    /// hashes with multiple threads, if any.
+    #[cfg(feature = "rayon")]
+    pub fn parallel_threshold(&self) -> Option<usize> {
+        match self.hasher {
+            Hasher::Blake3(_) => self.parallel_threshold,
+            Hasher::Dyn(_) => None,
+        }
+    }
+
+    /// algorithm returns the name of the digest algorithm.
+    pub fn algorithm(&self) -> &str {
+        &self.name
+    }
+
+    /// update hashes bytes.
+    pub fn update(&mut self, bytes: &[u8]) {
+        match &mut self.hasher {
+            Hasher::Dyn(hasher) => hasher.update(bytes),
+            #[cfg(feature = "rayon")]
+            Hasher::Blake3(hasher)
+                if self
+                    .parallel_threshold
+                    .is_some_and(|threshold| bytes.len() >= threshold) =>
+            {
+                hasher.update_rayon(bytes);
+                #[cfg(test)]
+                {
+                    self.parallel_updates += 1;
+                }
+            }
+            Hasher::Blake3(hasher) => {
+                hasher.update(bytes);
+            }
+        }
+    }
+
+    /// update_reader hashes the content of reader. Unlike `std::io::copy`, it
+    /// reads in chunks large enough for multithreaded hashing to kick in.
+    pub fn update_reader<R: Read>(&mut self, reader: R) -> Result<(), Error> {
+        digest_buffered(self, reader, DEFAULT_BUFFER_SIZE)
+    }
+
+    /// buffer_size returns the read buffer size to use when feeding the
+    /// digester from a reader, given the requested size. It is grown so that
+    /// multithreaded BLAKE3 hashing can kick in.
+    pub(crate) fn buffer_size(&self, requested: usize) -> usize {
+        #[cfg(feature = "rayon")]
+        if let Some(threshold) = self.parallel_threshold() {
+            if threshold <= MAX_PARALLEL_BUFFER_SIZE {
+                return requested.max(threshold);
+            }
+        }
+        requested
+    }
+
+    /// digest returns the digest of the bytes written so far, leaving the
+    /// digester usable for further writes.
+    pub fn digest(&self) -> Digest {
+        let encoded = match &self.hasher {
+            Hasher::Dyn(hasher) => hex::encode(hasher.box_clone().finalize()),
+            Hasher::Blake3(hasher) => hex::encode(hasher.finalize().as_bytes()),
+        };
+        Digest {
+            name: self.name.clone(),
+            digest: format!("{}:{}", self.name, encoded),
+        }
+    }
+
+    /// finalize consumes the digester and returns the digest of all bytes written.
+    pub fn finalize(self) -> Digest {
+        let encoded = match self.hasher {
+            Hasher::Dyn(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Blake3(hasher) => hex::encode(hasher.finalize().as_bytes()),
+        };
+        Digest {
+            digest: format!("{}:{}", self.name, encoded),
+            name: self.name,
+        }
+    }
+}
+
+impl Write for Digester {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+}
+
+/// TeeDigestWriter forwards writes to an inner writer while hashing the
+/// written bytes, so a blob can be stored and digested in one pass.
+pub struct TeeDigestWriter<W: Write> {
+    writer: W,
+    digester: Digester,
+    size: u64,
+}
+
+impl<W: Write> TeeDigestWriter<W> {
+    /// new returns a writer forwarding to writer and hashing with the named algorithm.
+    pub fn new(writer: W, algorithm: &str) -> Result<Self, Error> {
+        Ok(TeeDigestWriter {
+            writer,
+            digester: Digester::new(algorithm)?,
+            size: 0,
+        })
+    }
+
+    /// size returns the number of bytes written to the inner writer.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// digest returns the digest of the bytes written to the inner writer.
+    pub fn digest(&self) -> Digest {
+        self.digester.digest()
+    }
+
+    /// descriptor returns a descriptor of the written content with the given media type.
+    pub fn descriptor(&self, media_type: &str) -> Descriptor {
+        Descriptor {
+            media_type: Some(media_type.to_string()),
+            digest: Some(self.digest()),
+            size: self.size,
+            ..Default::default()
+        }
+    }
+
+    /// into_inner returns the inner writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Write for TeeDigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        let n = self.writer.write(buf)?;
+        self.digester.update(&buf[..n]);
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digester() {
+        let mut digester = Digester::new(SHA256).unwrap();
+        std::io::copy(&mut b"hel".as_ref(), &mut digester).unwrap();
+        digester.write_all(b"lo").unwrap();
+        let expected = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert_eq!(digester.digest().to_string(), expected);
+        assert_eq!(digester.finalize().to_string(), expected);
+    }
+
+    #[test]
+    fn test_digester_blake3() {
+        let mut digester = Digester::new(BLAKE3).unwrap();
+        digester.write_all(b"hello").unwrap();
+        assert_eq!(
+            digester.finalize().to_string(),
+            "blake3:ea8f163db38682925e4491c5e58d4bb3506ef8c14eb78a86e908c5624a67200f"
+        );
+        assert!(Digester::new("md5").is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_digester_blake3_parallel() {
+        let content = vec![3u8; 1024 * 1024];
+        let mut expected = Digester::new(BLAKE3).unwrap().with_parallel_threshold(None);
+        expected.write_all(&content).unwrap();
+        assert_eq!(expected.parallel_updates, 0);
+
+        let mut digester = Digester::new(BLAKE3)
+            .unwrap()
+            .with_parallel_threshold(Some(64 * 1024));
+        assert_eq!(digester.buffer_size(1024), 64 * 1024);
+        digester.update_reader(content.as_slice()).unwrap();
+        assert_eq!(digester.parallel_updates, 8);
+        assert_eq!(digester.finalize(), expected.finalize());
+
+        let digester = Digester::new(BLAKE3).unwrap();
+        assert_eq!(
+            digester.parallel_threshold(),
+            Algorithm::Blake3.parallel_threshold()
+        );
+        let digester = Digester::new(SHA256)
+            .unwrap()
+            .with_parallel_threshold(Some(1));
+        assert_eq!(digester.parallel_threshold(), None);
+    }
+
+    #[test]
+    fn test_digester_custom() {
+        use sha2::Digest as _;
+        super::super::algorithm::register_algorithm_with(
+            "test-digester-sha224",
+            224,
+            || Box::new(sha2::Sha224::new()),
+            |encoded| encoded.len() == 56,
+        )
+        .unwrap();
+        let mut digester = Digester::new("test-digester-sha224").unwrap();
+        digester.write_all(b"hello").unwrap();
+        let digest = digester.finalize();
+        assert_eq!(
+            digest.to_string(),
+            "test-digester-sha224:ea09ae9cc6768c50fcee903ed054556e5bfc8347907f12598aa24193"
+        );
+        assert!(digest.validate().is_ok());
+    }
+
+    #[test]
+    fn test_tee_digest_writer() {
+        let mut writer = TeeDigestWriter::new(Vec::new(), SHA256).unwrap();
+        writer.write_all(b"hello").unwrap();
+        assert_eq!(writer.size(), 5);
+        let descriptor = writer.descriptor("application/octet-stream");
+        assert_eq!(
+            descriptor.digest.unwrap().to_string(),
+            "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(descriptor.size, 5);
+        assert_eq!(writer.into_inner(), b"hello");
+    }
+}
@@ -0,0 +1,225 @@
+use super::digest::Digest;
+use crate::error::Error;
+use std::collections::HashMap;
+
+/// LookupError tells why DigestSet::lookup failed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LookupError {
+    /// The short identifier is empty or isn't an optional algorithm followed by a hex prefix.
+    #[error("invalid short digest")]
+    Invalid,
+    /// No digest of the set matches.
+    #[error("digest not found")]
+    NotFound,
+    /// More than one digest of the set matches.
+    #[error("ambiguous short digest")]
+    Ambiguous,
+}
+
+/// DigestSet holds digests and resolves short identifiers, such as the
+/// truncated IDs shown by `docker images`, to the digest they designate.
+#[derive(Debug, Clone, Default)]
+pub struct DigestSet {
+    // entries are sorted by encoded portion, then by algorithm.
+    entries: Vec<Digest>,
+}
+
+impl DigestSet {
+    /// new returns an empty set.
+    pub fn new() -> Self {
+        DigestSet::default()
+    }
+
+    /// add inserts digest, returning false if it was already present.
+    pub fn add(&mut self, digest: Digest) -> bool {
+        match self.search(&digest) {
+            Ok(_) => false,
+            Err(index) => {
+                self.entries.insert(index, digest);
+                true
+            }
+        }
+    }
+
+    /// remove deletes digest, returning false if it wasn't present.
+    pub fn remove(&mut self, digest: &Digest) -> bool {
+        match self.search(digest) {
+            Ok(index) => {
+                self.entries.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// contains reports whether digest is in the set.
+    pub fn contains(&self, digest: &Digest) -> bool {
+        self.search(digest).is_ok()
+    }
+
+    /// len returns the number of digests in the set.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// is_empty reports whether the set holds no digest.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// iter returns the digests of the set, ordered by encoded portion.
+    pub fn iter(&self) -> impl Iterator<Item = &Digest> {
+        self.entries.iter()
+    }
+
+    /// lookup returns the only digest matching short, which is a hex prefix of
+    /// the encoded portion, optionally preceded by the algorithm and `:`, e.g.
+    /// `2cf24d` or `sha256:2cf24d`. A complete digest always resolves to
+    /// itself, even if it is also the prefix of another one. Failures are
+    /// reported as `Error::Lookup`.
+    pub fn lookup(&self, short: &str) -> Result<Digest, Error> {
+        let (algorithm, prefix) = match short.split_once(':') {
+            Some((algorithm, prefix)) => (Some(algorithm), prefix),
+            None => (None, short),
+        };
+        if prefix.is_empty() || !prefix.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(LookupError::Invalid.into());
+        }
+        let start = self
+            .entries
+            .partition_point(|digest| digest.parts().1 < prefix);
+        let matches = self.entries[start..]
+            .iter()
+            .take_while(|digest| digest.parts().1.starts_with(prefix))
+            .filter(|digest| algorithm.is_none() || algorithm == Some(digest.parts().0))
+            .collect::<Vec<_>>();
+        let exact = matches
+            .iter()
+            .filter(|digest| digest.parts().1 == prefix)
+            .copied()
+            .collect::<Vec<_>>();
+        match (matches.as_slice(), exact.as_slice()) {
+            ([], _) => Err(LookupError::NotFound.into()),
+            ([digest], _) | (_, [digest]) => Ok((*digest).clone()),
+            _ => Err(LookupError::Ambiguous.into()),
+        }
+    }
+
+    /// short_codes returns the shortest prefix, of at least min_length
+    /// characters, identifying each digest of the set through lookup. Digests
+    /// whose encoded portion is shared with another algorithm are identified
+    /// by their complete form.
+    pub fn short_codes(&self, min_length: usize) -> HashMap<Digest, String> {
+        let common = |a: &Digest, b: &Digest| {
+            a.parts()
+                .1
+                .bytes()
+                .zip(b.parts().1.bytes())
+                .take_while(|(a, b)| a == b)
+                .count()
+        };
+        let mut codes = HashMap::with_capacity(self.entries.len());
+        for (i, digest) in self.entries.iter().enumerate() {
+            let encoded = digest.parts().1;
+            let mut length = min_length.max(1);
+            if i > 0 {
+                length = length.max(common(&self.entries[i - 1], digest) + 1);
+            }
+            if let Some(next) = self.entries.get(i + 1) {
+                length = length.max(common(digest, next) + 1);
+            }
+            let code = if length > encoded.len() {
+                digest.to_string()
+            } else {
+                encoded[..length].to_string()
+            };
+            codes.insert(digest.clone(), code);
+        }
+        codes
+    }
+
+    fn search(&self, digest: &Digest) -> Result<usize, usize> {
+        self.entries.binary_search_by(|entry| {
+            let (algorithm, encoded) = entry.parts();
+            let (other_algorithm, other_encoded) = digest.parts();
+            (encoded, algorithm).cmp(&(other_encoded, other_algorithm))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(algorithm: &str, encoded: &str) -> Digest {
+        let size = if algorithm == "sha512" { 128 } else { 64 };
+        format!("{}:{:0<width$}", algorithm, encoded, width = size)
+            .parse()
+            .unwrap()
+    }
+
+    fn kind(err: Error) -> LookupError {
+        match err {
+            Error::Lookup(err) => err,
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut set = DigestSet::new();
+        let a = digest("sha256", "1234abcd");
+        let b = digest("sha256", "1234abce");
+        let c = digest("sha512", "1234abcd");
+        let d = digest("sha256", "5678");
+        for digest in [&a, &b, &c, &d] {
+            assert!(set.add(digest.clone()));
+        }
+        assert!(!set.add(a.clone()));
+        assert_eq!(set.len(), 4);
+
+        assert_eq!(set.lookup("5").unwrap(), d);
+        assert_eq!(set.lookup("sha256:1234abce").unwrap(), b);
+        assert_eq!(set.lookup(&a.to_string()).unwrap(), a);
+        assert_eq!(set.lookup("sha512:1").unwrap(), c);
+        assert_eq!(
+            kind(set.lookup("1234abcd").unwrap_err()),
+            LookupError::Ambiguous
+        );
+        assert_eq!(
+            kind(set.lookup("sha256:1234").unwrap_err()),
+            LookupError::Ambiguous
+        );
+        assert_eq!(kind(set.lookup("9").unwrap_err()), LookupError::NotFound);
+        assert_eq!(kind(set.lookup("").unwrap_err()), LookupError::Invalid);
+        assert_eq!(kind(set.lookup("xyz").unwrap_err()), LookupError::Invalid);
+
+        assert!(set.remove(&b));
+        assert!(!set.contains(&b));
+        assert_eq!(set.lookup("sha256:1234").unwrap(), a);
+    }
+
+    #[test]
+    fn test_short_codes() {
+        let mut set = DigestSet::new();
+        let a = digest("sha256", "1234abcd");
+        let b = digest("sha256", "1234abce");
+        let c = digest("sha512", "1234abcd");
+        let d = digest("sha256", "5678");
+        let e = digest("sha512", "5678");
+        let f = digest("sha256", "9abc");
+        for digest in [&a, &b, &c, &d, &e, &f] {
+            set.add(digest.clone());
+        }
+        let codes = set.short_codes(3);
+        assert_eq!(codes[&d], d.to_string());
+        assert_eq!(codes[&e].len(), 65);
+        assert_eq!(codes[&f], "9ab");
+        assert_eq!(codes[&b], "1234abce");
+        assert_eq!(codes[&a], a.to_string());
+        assert_eq!(codes[&c].len(), 65);
+        for (digest, code) in &codes {
+            assert_eq!(&set.lookup(code).unwrap(), digest);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use super::algorithm::Algorithm;
+use super::digester::Digester;
+use crate::error::Error;
+use memmap2::Mmap;
+
+// SLICE_SIZE is the size of the slices of the mapping handed to the digester.
+const SLICE_SIZE: usize = 1024 * 1024;
+
+impl Algorithm {
+    /// from_file_mmap digests the file like from_file, but maps it into memory
+    /// instead of reading it, which is faster for very large blobs. The file
+    /// must not be modified while it is being digested.
+    pub fn from_file_mmap(&self, path: &str) -> Result<String, Error> {
+        let mut digester = Digester::new(self.as_str())?;
+        let file = std::fs::File::open(path)?;
+        // Empty files can't be mapped.
+        if file.metadata()?.len() > 0 {
+            // SAFETY: the mapping is read-only and callers must not modify the
+            // file while it is mapped.
+            let map = unsafe { Mmap::map(&file)? };
+            for slice in map.chunks(SLICE_SIZE) {
+                digester.update(slice);
+            }
+        }
+        Ok(digester.finalize().encoded())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::algorithm::{Algorithms, CryptoHash, BLAKE3, SHA256};
+
+    #[test]
+    fn test_from_file_mmap() {
+        let algs = Algorithms::new();
+        let path = std::env::temp_dir().join("oci-image-spec-mmap-test");
+        for content in [&b""[..], &[1u8; 3 * 1024 * 1024 + 7][..]] {
+            std::fs::write(&path, content).unwrap();
+            for name in [SHA256, BLAKE3] {
+                let alg = algs.get_algorithm(name).unwrap();
+                assert_eq!(
+                    alg.from_file_mmap(path.to_str().unwrap()).unwrap(),
+                    alg.from_bytes(content).unwrap()
+                );
+            }
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,2 +1,16 @@
 pub mod algorithm;
+#[cfg(feature = "async")]
+pub mod async_digest;
+pub mod cache;
 pub mod digest;
+pub mod digester;
+pub mod digestset;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "multihash")]
+pub mod multihash;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "stream")]
+pub mod stream;
+pub mod verifier;
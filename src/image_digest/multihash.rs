@@ -0,0 +1,159 @@
+use super::algorithm::{BLAKE3, SHA256, SHA384, SHA3_256, SHA3_512, SHA512};
+use super::digest::Digest;
+use crate::error::Error;
+
+/// CODEC_RAW is the multicodec of raw binary content, used for OCI blobs.
+pub const CODEC_RAW: u64 = 0x55;
+
+// MULTIHASH_CODES maps digest algorithms to their multihash codes.
+const MULTIHASH_CODES: [(&str, u64); 6] = [
+    (SHA256, 0x12),
+    (SHA512, 0x13),
+    (SHA3_512, 0x14),
+    (SHA3_256, 0x16),
+    (SHA384, 0x20),
+    (BLAKE3, 0x1e),
+];
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+fn invalid_data(message: &str) -> Error {
+    Error::InvalidData(message.to_string())
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn read_varint(buf: &mut &[u8]) -> Result<u64, Error> {
+    let mut value = 0u64;
+    for (i, byte) in buf.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            *buf = &buf[i + 1..];
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("invalid varint"))
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in bytes {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(s: &str) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in s.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a == c.to_ascii_lowercase())
+            .ok_or_else(|| invalid_data("invalid base32 character"))?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+impl Digest {
+    /// to_multihash returns the binary multihash of the digest.
+    pub fn to_multihash(&self) -> Result<Vec<u8>, Error> {
+        let algorithm = self.algorithm();
+        let code = MULTIHASH_CODES
+            .iter()
+            .find(|(name, _)| *name == algorithm)
+            .map(|(_, code)| *code)
+            .ok_or_else(|| invalid_data("digest algorithm has no multihash code"))?;
+        let bytes = hex::decode(self.clone().encoded())
+            .map_err(|_| invalid_data("digest is not hex encoded"))?;
+        let mut buf = Vec::with_capacity(bytes.len() + 4);
+        write_varint(&mut buf, code);
+        write_varint(&mut buf, bytes.len() as u64);
+        buf.extend_from_slice(&bytes);
+        Ok(buf)
+    }
+
+    /// from_multihash parses a binary multihash into a digest.
+    pub fn from_multihash(mut bytes: &[u8]) -> Result<Digest, Error> {
+        let code = read_varint(&mut bytes)?;
+        let len = read_varint(&mut bytes)?;
+        let name = MULTIHASH_CODES
+            .iter()
+            .find(|(_, c)| *c == code)
+            .map(|(name, _)| *name)
+            .ok_or_else(|| invalid_data("unsupported multihash code"))?;
+        if bytes.len() as u64 != len {
+            return Err(invalid_data("multihash length mismatch"));
+        }
+        format!("{}:{}", name, hex::encode(bytes)).parse()
+    }
+
+    /// to_cid returns the digest as a base32 encoded CIDv1 with the given
+    /// content codec, usually `CODEC_RAW`.
+    pub fn to_cid(&self, codec: u64) -> Result<String, Error> {
+        let mut buf = Vec::new();
+        write_varint(&mut buf, 1);
+        write_varint(&mut buf, codec);
+        buf.extend(self.to_multihash()?);
+        Ok(format!("b{}", base32_encode(&buf)))
+    }
+
+    /// from_cid parses a base32 encoded CIDv1 into a digest, returning the
+    /// digest and the content codec of the CID.
+    pub fn from_cid(cid: &str) -> Result<(Digest, u64), Error> {
+        let encoded = cid
+            .strip_prefix('b')
+            .ok_or_else(|| invalid_data("only base32 encoded CIDv1 is supported"))?;
+        let bytes = base32_decode(encoded)?;
+        let mut bytes = &bytes[..];
+        if read_varint(&mut bytes)? != 1 {
+            return Err(invalid_data("only CIDv1 is supported"));
+        }
+        let codec = read_varint(&mut bytes)?;
+        Ok((Digest::from_multihash(bytes)?, codec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO: &str = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn test_multihash() {
+        let digest: Digest = HELLO.parse().unwrap();
+        let multihash = digest.to_multihash().unwrap();
+        assert_eq!(&multihash[..2], &[0x12, 0x20]);
+        assert_eq!(Digest::from_multihash(&multihash).unwrap(), digest);
+    }
+
+    #[test]
+    fn test_cid() {
+        let digest: Digest = HELLO.parse().unwrap();
+        let cid = digest.to_cid(CODEC_RAW).unwrap();
+        assert!(cid.starts_with("bafkrei"));
+        assert_eq!(Digest::from_cid(&cid).unwrap(), (digest, CODEC_RAW));
+    }
+}
@@ -0,0 +1,93 @@
+use super::algorithm::{digest_buffered, Algorithms, CANONICAL, DEFAULT_BUFFER_SIZE};
+use super::digest::Digest;
+use super::digest::DigestParseError;
+use super::digester::Digester;
+use crate::error::Error;
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+
+impl Algorithms {
+    /// digest_files digests the files in parallel with the canonical
+    /// algorithm, returning one result per path, in order.
+    pub fn digest_files(&self, paths: &[PathBuf]) -> Vec<Result<Digest, Error>> {
+        self.digest_files_with(CANONICAL, paths)
+    }
+
+    /// digest_files_with digests the files in parallel with the named algorithm,
+    /// returning one result per path, in order. Custom algorithms are resolved
+    /// in this registry.
+    pub fn digest_files_with(
+        &self,
+        algorithm: &str,
+        paths: &[PathBuf],
+    ) -> Vec<Result<Digest, Error>> {
+        if self.get_algorithm(algorithm).is_none() {
+            return paths
+                .iter()
+                .map(|_| Err(DigestParseError::UnknownAlgorithm(algorithm.to_string()).into()))
+                .collect();
+        }
+        paths
+            .par_iter()
+            .map(|path| digest_file(Digester::new_with(algorithm, self)?, path))
+            .collect()
+    }
+}
+
+fn digest_file(mut digester: Digester, path: &Path) -> Result<Digest, Error> {
+    digest_buffered(
+        &mut digester,
+        std::fs::File::open(path)?,
+        DEFAULT_BUFFER_SIZE,
+    )?;
+    Ok(digester.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image_digest::algorithm::CryptoHash;
+
+    #[test]
+    fn test_digest_files() {
+        let dir = std::env::temp_dir().join("oci-image-spec-parallel-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut paths = (0..8)
+            .map(|i| {
+                let path = dir.join(i.to_string());
+                std::fs::write(&path, i.to_string()).unwrap();
+                path
+            })
+            .collect::<Vec<_>>();
+        paths.push(dir.join("missing"));
+
+        let algs = Algorithms::new();
+        let results = algs.digest_files(&paths);
+        let alg = algs.get_algorithm(CANONICAL).unwrap();
+        for (i, result) in results.iter().take(8).enumerate() {
+            assert_eq!(
+                result.as_ref().unwrap().to_string(),
+                format!("sha256:{}", alg.from_string(&i.to_string()).unwrap())
+            );
+        }
+        assert!(results[8].is_err());
+        assert!(algs.digest_files_with("md5", &paths)[0].is_err());
+
+        let mut local = Algorithms::new();
+        local
+            .register_algorithm_with(
+                "test-local-sha224",
+                224,
+                || Box::new(<sha2::Sha224 as sha2::Digest>::new()),
+                |encoded| encoded.len() == 56,
+            )
+            .unwrap();
+        let results = local.digest_files_with("test-local-sha224", &paths[..1]);
+        assert_eq!(
+            results[0].as_ref().unwrap().to_string(),
+            "test-local-sha224:dfd5f9139a820075df69d7895015360b76d0360f3d4b77a845689614"
+        );
+        assert!(algs.digest_files_with("test-local-sha224", &paths)[0].is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
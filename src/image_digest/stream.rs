@@ -0,0 +1,104 @@
+use super::digest::Digest;
+use super::digester::Digester;
+use crate::error::Error;
+use futures::stream::{Stream, StreamExt};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// digest_stream consumes a stream of chunks, such as an HTTP body of
+/// `bytes::Bytes`, and returns the digest and size of its content. Errors of
+/// the stream are converted into this crate's Error.
+pub async fn digest_stream<S, B, E>(algorithm: &str, mut stream: S) -> Result<(Digest, u64), Error>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Into<Error>,
+{
+    let mut digester = Digester::new(algorithm)?;
+    let mut size = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(Into::into)?;
+        digester.update(chunk.as_ref());
+        size += chunk.as_ref().len() as u64;
+    }
+    Ok((digester.finalize(), size))
+}
+
+/// DigestStream re-yields the chunks of an inner stream while hashing them,
+/// so content can be passed downstream and digested in one pass.
+pub struct DigestStream<S> {
+    stream: S,
+    digester: Digester,
+    size: u64,
+}
+
+impl<S> DigestStream<S> {
+    /// new returns a stream hashing the chunks of stream with the named algorithm.
+    pub fn new(stream: S, algorithm: &str) -> Result<Self, Error> {
+        Ok(DigestStream {
+            stream,
+            digester: Digester::new(algorithm)?,
+            size: 0,
+        })
+    }
+
+    /// size returns the number of bytes yielded so far.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// digest returns the digest of the bytes yielded so far.
+    pub fn digest(&self) -> Digest {
+        self.digester.digest()
+    }
+}
+
+impl<S, B, E> Stream for DigestStream<S>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    type Item = Result<B, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let poll = this.stream.poll_next_unpin(cx);
+        if let Poll::Ready(Some(Ok(chunk))) = &poll {
+            this.digester.update(chunk.as_ref());
+            this.size += chunk.as_ref().len() as u64;
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::algorithm::SHA256;
+    use super::*;
+    use bytes::Bytes;
+
+    const HELLO: &str = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn chunks() -> impl Stream<Item = Result<Bytes, std::io::Error>> + Unpin {
+        futures::stream::iter(vec![Ok(Bytes::from("hel")), Ok(Bytes::from("lo"))])
+    }
+
+    #[tokio::test]
+    async fn test_digest_stream() {
+        let (digest, size) = digest_stream(SHA256, chunks()).await.unwrap();
+        assert_eq!(digest.to_string(), HELLO);
+        assert_eq!(size, 5);
+    }
+
+    #[tokio::test]
+    async fn test_digest_stream_passthrough() {
+        let mut stream = DigestStream::new(chunks(), SHA256).unwrap();
+        let mut content = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            content.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(content, b"hello");
+        assert_eq!(stream.size(), 5);
+        assert_eq!(stream.digest().to_string(), HELLO);
+    }
+}
@@ -0,0 +1,133 @@
+use super::digest::Digest;
+use super::digester::Digester;
+use crate::error::Error;
+use crate::specs::v1::descriptor::VerifyError;
+use std::io::{Read, Write};
+use subtle::ConstantTimeEq;
+
+/// Verifier checks that the content written to it matches an expected digest,
+/// without having to hold the content in memory.
+pub struct Verifier {
+    expected: Digest,
+    digester: Digester,
+}
+
+impl Verifier {
+    /// new returns a verifier for the expected digest. It fails if the digest
+    /// algorithm isn't supported.
+    pub fn new(expected: &Digest) -> Result<Self, Error> {
+        Ok(Verifier {
+            digester: Digester::new(&expected.algorithm())?,
+            expected: expected.clone(),
+        })
+    }
+
+    /// verified reports whether the content written so far matches the
+    /// expected digest. The digests are compared in constant time.
+    pub fn verified(&self) -> bool {
+        let computed = self.digester.digest();
+        computed
+            .digest
+            .as_bytes()
+            .ct_eq(self.expected.digest.as_bytes())
+            .into()
+    }
+
+    /// digest returns the digest of the content written so far.
+    pub fn digest(&self) -> Digest {
+        self.digester.digest()
+    }
+}
+
+impl Write for Verifier {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        self.digester.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+}
+
+/// VerifyReader hashes the bytes read through it and fails the read which
+/// reaches end of file if the content doesn't match the expected digest.
+pub struct VerifyReader<R: Read> {
+    reader: R,
+    verifier: Verifier,
+}
+
+impl<R: Read> VerifyReader<R> {
+    /// new returns a reader verifying the content of reader against expected.
+    pub fn new(reader: R, expected: &Digest) -> Result<Self, Error> {
+        Ok(VerifyReader {
+            reader,
+            verifier: Verifier::new(expected)?,
+        })
+    }
+
+    /// into_inner returns the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Read for VerifyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        let n = self.reader.read(buf)?;
+        if n > 0 {
+            self.verifier.write_all(&buf[..n])?;
+        } else if !buf.is_empty() && !self.verifier.verified() {
+            let err = Error::from(VerifyError::DigestMismatch {
+                expected: self.verifier.expected.clone(),
+                actual: self.verifier.digest(),
+            });
+            return Err(err.into());
+        }
+        Ok(n)
+    }
+}
+
+impl Digest {
+    /// verifier returns a Verifier for the digest.
+    pub fn verifier(&self) -> Result<Verifier, Error> {
+        Verifier::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verifier() {
+        let digest: Digest =
+            "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+                .parse()
+                .unwrap();
+        let mut verifier = digest.verifier().unwrap();
+        verifier.write_all(b"hell").unwrap();
+        assert!(!verifier.verified());
+        verifier.write_all(b"o").unwrap();
+        assert!(verifier.verified());
+    }
+
+    #[test]
+    fn test_verify_reader() {
+        let digest: Digest =
+            "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+                .parse()
+                .unwrap();
+        let mut content = String::new();
+        VerifyReader::new(b"hello".as_ref(), &digest)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "hello");
+
+        let err = VerifyReader::new(b"hullo".as_ref(), &digest)
+            .unwrap()
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -1,2 +1,6 @@
+pub mod clock;
+pub mod error;
+pub mod export;
 pub mod image_digest;
 pub mod specs;
+pub mod vfs;
@@ -1,3 +1,4 @@
 pub mod v1;
+pub mod v1_1;
 pub mod version;
 pub mod versioned;
@@ -0,0 +1,243 @@
+use super::descriptor::Descriptor;
+use super::index::Index;
+use super::manifest::Manifest;
+
+/// MEDIA_TYPE_SOCI_INDEX is the artifact type of a SOCI index manifest, also used as
+/// its config media type when stored as a plain image manifest.
+pub const MEDIA_TYPE_SOCI_INDEX: &str = "application/vnd.amazon.soci.index.v1+json";
+
+/// MEDIA_TYPE_SOCI_ZTOC is the media type of the zTOC layers of a SOCI index.
+pub const MEDIA_TYPE_SOCI_ZTOC: &str = "application/octet-stream";
+
+/// ANNOTATION_SOCI_IMAGE_LAYER_DIGEST is the annotation key on a zTOC layer for the
+/// digest of the image layer it indexes.
+pub const ANNOTATION_SOCI_IMAGE_LAYER_DIGEST: &str = "com.amazon.soci.image-layer-digest";
+
+/// ANNOTATION_SOCI_IMAGE_LAYER_MEDIA_TYPE is the annotation key on a zTOC layer for the
+/// media type of the image layer it indexes.
+pub const ANNOTATION_SOCI_IMAGE_LAYER_MEDIA_TYPE: &str = "com.amazon.soci.image-layer-mediatype";
+
+/// ANNOTATION_SOCI_BUILD_TOOL_IDENTIFIER is the annotation key on a SOCI index for the
+/// tool which built it.
+pub const ANNOTATION_SOCI_BUILD_TOOL_IDENTIFIER: &str = "com.amazon.soci.build-tool-identifier";
+
+/// ANNOTATION_SOCI_INDEX_DIGEST is the annotation key on an image manifest for the
+/// digest of the SOCI index which accelerates it.
+pub const ANNOTATION_SOCI_INDEX_DIGEST: &str = "com.amazon.soci.index-digest";
+
+/// MEDIA_TYPE_NYDUS_BLOB is the media type of Nydus data blob layers.
+pub const MEDIA_TYPE_NYDUS_BLOB: &str = "application/vnd.oci.image.layer.nydus.blob.v1";
+
+/// ANNOTATION_NYDUS_BOOTSTRAP is the annotation key marking the Nydus bootstrap (metadata) layer.
+pub const ANNOTATION_NYDUS_BOOTSTRAP: &str = "containerd.io/snapshot/nydus-bootstrap";
+
+/// ANNOTATION_NYDUS_BLOB is the annotation key marking Nydus data blob layers.
+pub const ANNOTATION_NYDUS_BLOB: &str = "containerd.io/snapshot/nydus-blob";
+
+/// ANNOTATION_NYDUS_RAFS_VERSION is the annotation key on the bootstrap layer for the RAFS format version.
+pub const ANNOTATION_NYDUS_RAFS_VERSION: &str = "containerd.io/snapshot/nydus-rafs-version";
+
+/// NYDUS_OS_FEATURE is the `os.features` entry of index platforms referring to Nydus manifests.
+pub const NYDUS_OS_FEATURE: &str = "nydus.remoteimage.v1";
+
+fn annotation<'a>(descriptor: &'a Descriptor, key: &str) -> Option<&'a String> {
+    descriptor
+        .annotations
+        .as_ref()
+        .and_then(|annotations| annotations.get(key))
+}
+
+/// is_soci_index reports whether the manifest is a SOCI index.
+pub fn is_soci_index(manifest: &Manifest) -> bool {
+    manifest.config.media_type.as_deref() == Some(MEDIA_TYPE_SOCI_INDEX)
+}
+
+/// soci_ztoc_layers returns the zTOC layers of a SOCI index together with the
+/// digest of the image layer each of them indexes.
+pub fn soci_ztoc_layers(manifest: &Manifest) -> Vec<(&Descriptor, &str)> {
+    manifest
+        .layers
+        .iter()
+        .filter_map(|layer| {
+            annotation(layer, ANNOTATION_SOCI_IMAGE_LAYER_DIGEST)
+                .map(|digest| (layer, digest.as_str()))
+        })
+        .collect()
+}
+
+/// soci_indexes returns the SOCI indexes among the referrers of an image
+/// manifest, recognized by their artifact type.
+pub fn soci_indexes(referrers: &Index) -> Vec<&Descriptor> {
+    referrers
+        .manifests
+        .iter()
+        .filter(|descriptor| descriptor.artifact_type.as_deref() == Some(MEDIA_TYPE_SOCI_INDEX))
+        .collect()
+}
+
+/// is_nydus_bootstrap reports whether the layer is a Nydus bootstrap layer.
+pub fn is_nydus_bootstrap(descriptor: &Descriptor) -> bool {
+    annotation(descriptor, ANNOTATION_NYDUS_BOOTSTRAP).map(String::as_str) == Some("true")
+}
+
+/// is_nydus_blob reports whether the layer is a Nydus data blob.
+pub fn is_nydus_blob(descriptor: &Descriptor) -> bool {
+    descriptor.media_type.as_deref() == Some(MEDIA_TYPE_NYDUS_BLOB)
+        || annotation(descriptor, ANNOTATION_NYDUS_BLOB).map(String::as_str) == Some("true")
+}
+
+/// nydus_bootstrap returns the Nydus bootstrap layer of a manifest, if any.
+pub fn nydus_bootstrap(manifest: &Manifest) -> Option<&Descriptor> {
+    manifest
+        .layers
+        .iter()
+        .rev()
+        .find(|layer| is_nydus_bootstrap(layer))
+}
+
+/// nydus_manifests returns the manifests of an index whose platform declares the Nydus os feature.
+pub fn nydus_manifests(index: &Index) -> Vec<&Descriptor> {
+    index
+        .manifests
+        .iter()
+        .filter(|descriptor| {
+            descriptor
+                .platform
+                .as_ref()
+                .and_then(|platform| platform.os_features.as_ref())
+                .is_some_and(|features| features.iter().any(|f| f == NYDUS_OS_FEATURE))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::specs::v1::validation::Validate;
+
+    fn digest(fill: &str) -> String {
+        format!("sha256:{}", fill.repeat(64))
+    }
+
+    #[test]
+    fn test_soci() {
+        let manifest: Manifest = serde_json::from_str(&format!(
+            r#"{{
+                "schemaVersion": 2,
+                "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                "config": {{
+                    "mediaType": "application/vnd.amazon.soci.index.v1+json",
+                    "digest": "{}",
+                    "size": 2
+                }},
+                "layers": [
+                    {{
+                        "mediaType": "application/octet-stream",
+                        "digest": "{}",
+                        "size": 1024,
+                        "annotations": {{
+                            "com.amazon.soci.image-layer-digest": "{}",
+                            "com.amazon.soci.image-layer-mediatype": "application/vnd.oci.image.layer.v1.tar+gzip"
+                        }}
+                    }}
+                ],
+                "annotations": {{"com.amazon.soci.build-tool-identifier": "AWS SOCI CLI v0.4"}}
+            }}"#,
+            digest("a"),
+            digest("b"),
+            digest("c")
+        ))
+        .unwrap();
+        assert!(is_soci_index(&manifest));
+        let ztocs = soci_ztoc_layers(&manifest);
+        assert_eq!(ztocs.len(), 1);
+        assert_eq!(ztocs[0].1, digest("c"));
+        assert!(!is_soci_index(&Manifest::default()));
+
+        let referrers = Index {
+            schema_version: 2,
+            manifests: vec![
+                Descriptor {
+                    artifact_type: Some(MEDIA_TYPE_SOCI_INDEX.to_string()),
+                    ..Default::default()
+                },
+                Descriptor {
+                    artifact_type: Some("application/vnd.example.sbom.v1+json".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(soci_indexes(&referrers), vec![&referrers.manifests[0]]);
+    }
+
+    #[test]
+    fn test_nydus() {
+        let manifest: Manifest = serde_json::from_str(&format!(
+            r#"{{
+                "schemaVersion": 2,
+                "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                "config": {{
+                    "mediaType": "application/vnd.oci.image.config.v1+json",
+                    "digest": "{}",
+                    "size": 2
+                }},
+                "layers": [
+                    {{
+                        "mediaType": "application/vnd.oci.image.layer.nydus.blob.v1",
+                        "digest": "{}",
+                        "size": 4096,
+                        "annotations": {{"containerd.io/snapshot/nydus-blob": "true"}}
+                    }},
+                    {{
+                        "mediaType": "application/vnd.oci.image.layer.v1.tar+gzip",
+                        "digest": "{}",
+                        "size": 512,
+                        "annotations": {{
+                            "containerd.io/snapshot/nydus-bootstrap": "true",
+                            "containerd.io/snapshot/nydus-rafs-version": "6"
+                        }}
+                    }}
+                ]
+            }}"#,
+            digest("a"),
+            digest("b"),
+            digest("c")
+        ))
+        .unwrap();
+        assert!(is_nydus_blob(&manifest.layers[0]));
+        assert!(!is_nydus_bootstrap(&manifest.layers[0]));
+        assert!(is_nydus_bootstrap(&manifest.layers[1]));
+        assert!(!is_nydus_blob(&manifest.layers[1]));
+        assert_eq!(nydus_bootstrap(&manifest), Some(&manifest.layers[1]));
+        assert!(manifest.validate().errors.is_empty());
+
+        let index: Index = serde_json::from_str(&format!(
+            r#"{{
+                "schemaVersion": 2,
+                "manifests": [
+                    {{
+                        "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                        "digest": "{}",
+                        "size": 10,
+                        "platform": {{"architecture": "amd64", "os": "linux"}}
+                    }},
+                    {{
+                        "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                        "digest": "{}",
+                        "size": 10,
+                        "platform": {{
+                            "architecture": "amd64",
+                            "os": "linux",
+                            "os.features": ["nydus.remoteimage.v1"]
+                        }}
+                    }}
+                ]
+            }}"#,
+            digest("d"),
+            digest("e")
+        ))
+        .unwrap();
+        assert_eq!(nydus_manifests(&index), vec![&index.manifests[1]]);
+    }
+}
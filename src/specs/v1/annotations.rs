@@ -1,3 +1,9 @@
+use super::acceleration::{
+    ANNOTATION_NYDUS_BLOB, ANNOTATION_NYDUS_BOOTSTRAP, ANNOTATION_NYDUS_RAFS_VERSION,
+};
+use crate::error::Error;
+use std::collections::HashMap;
+
 // AnnotationCreated is the annotation key for the date and time on which the image was built (date-time string as defined by RFC 3339).
 pub const ANNOTATION_CREATED: &str = "org.opencontainers.image.created";
 
@@ -42,3 +48,205 @@ pub const ANNOTATION_BASE_IMAGE_DIGEST: &str = "org.opencontainers.image.base.di
 
 // AnnotationBaseImageName is the annotation key for the image reference of the image's base image.
 pub const ANNOTATION_BASE_IMAGE_NAME: &str = "org.opencontainers.image.base.name";
+
+// OCI_NAMESPACE is the annotation key prefix reserved for keys defined by OCI specifications.
+const OCI_NAMESPACE: &str = "org.opencontainers";
+
+// PREDEFINED_ANNOTATIONS lists the pre-defined annotation keys of the image spec.
+const PREDEFINED_ANNOTATIONS: [&str; 14] = [
+    ANNOTATION_CREATED,
+    ANNOTATION_AUTHORS,
+    ANNOTATION_URL,
+    ANNOTATION_DOCUMENTATION,
+    ANNOTATION_SOURCE,
+    ANNOTATION_VERSION,
+    ANNOTATION_REVISION,
+    ANNOTATION_VENDOR,
+    ANNOTATION_LICENSES,
+    ANNOTATION_REF_NAME,
+    ANNOTATION_TITLE,
+    ANNOTATION_DESCRIPTION,
+    ANNOTATION_BASE_IMAGE_DIGEST,
+    ANNOTATION_BASE_IMAGE_NAME,
+];
+
+// KNOWN_FOREIGN_ANNOTATIONS lists widely used annotation keys which don't
+// follow reverse domain notation, such as the Nydus layer keys, and aren't linted.
+const KNOWN_FOREIGN_ANNOTATIONS: [&str; 3] = [
+    ANNOTATION_NYDUS_BOOTSTRAP,
+    ANNOTATION_NYDUS_BLOB,
+    ANNOTATION_NYDUS_RAFS_VERSION,
+];
+
+/// validate_namespace checks that a vendor namespace uses reverse domain
+/// notation (e.g. `com.example`) and is not inside the `org.opencontainers`
+/// namespace, which is reserved for keys defined by OCI specifications.
+pub fn validate_namespace(namespace: &str) -> Result<(), Error> {
+    if !is_reverse_dns(namespace) {
+        return Err(Error::InvalidInput(format!(
+            "annotation namespace {} is not in reverse domain notation",
+            namespace
+        )));
+    }
+    if namespace == OCI_NAMESPACE || namespace.starts_with(&format!("{}.", OCI_NAMESPACE)) {
+        return Err(Error::InvalidInput(format!(
+            "annotation namespace {} is reserved for OCI specifications",
+            namespace
+        )));
+    }
+    Ok(())
+}
+
+/// lint_keys returns the key and a warning for each annotation key that is
+/// not in reverse domain notation or uses the reserved `org.opencontainers`
+/// namespace without being a pre-defined key, ordered by key.
+pub fn lint_keys(annotations: &HashMap<String, String>) -> Vec<(String, String)> {
+    let mut keys = annotations.keys().collect::<Vec<_>>();
+    keys.sort();
+    keys.into_iter()
+        .filter_map(|key| {
+            let warning = if key.starts_with(&format!("{}.", OCI_NAMESPACE)) {
+                if PREDEFINED_ANNOTATIONS.contains(&key.as_str()) {
+                    return None;
+                }
+                format!(
+                    "annotation key {} uses the reserved {} namespace",
+                    key, OCI_NAMESPACE
+                )
+            } else if !KNOWN_FOREIGN_ANNOTATIONS.contains(&key.as_str())
+                && !key
+                    .rsplit_once('.')
+                    .is_some_and(|(namespace, _)| is_reverse_dns(namespace))
+            {
+                format!("annotation key {} is not in reverse domain notation", key)
+            } else {
+                return None;
+            };
+            Some((key.clone(), warning))
+        })
+        .collect()
+}
+
+// is_reverse_dns reports whether value has at least two dot separated
+// lower case DNS labels, e.g. `com.example`.
+fn is_reverse_dns(value: &str) -> bool {
+    let labels = value.split('.').collect::<Vec<_>>();
+    labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+/// Annotations provides namespaced access to an annotation map.
+pub trait Annotations {
+    /// vendor returns a view of the annotations scoped to `namespace`, which
+    /// must pass `validate_namespace`.
+    fn vendor(&mut self, namespace: &str) -> Result<VendorAnnotations<'_>, Error>;
+}
+
+impl Annotations for HashMap<String, String> {
+    fn vendor(&mut self, namespace: &str) -> Result<VendorAnnotations<'_>, Error> {
+        validate_namespace(namespace)?;
+        Ok(VendorAnnotations {
+            namespace: namespace.to_string(),
+            annotations: self,
+        })
+    }
+}
+
+/// VendorAnnotations reads and writes annotation keys under a vendor namespace,
+/// e.g. `set("build", ..)` in the `com.example` namespace sets `com.example.build`.
+#[derive(Debug)]
+pub struct VendorAnnotations<'a> {
+    namespace: String,
+    annotations: &'a mut HashMap<String, String>,
+}
+
+impl VendorAnnotations<'_> {
+    /// key returns the fully qualified annotation key for `name`.
+    pub fn key(&self, name: &str) -> String {
+        format!("{}.{}", self.namespace, name)
+    }
+
+    /// set sets the annotation `name` in the namespace, returning the previous value.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<Option<String>, Error> {
+        if name.is_empty() || name.starts_with('.') || name.ends_with('.') {
+            return Err(Error::InvalidInput(format!(
+                "invalid annotation key name {:?}",
+                name
+            )));
+        }
+        Ok(self.annotations.insert(self.key(name), value.to_string()))
+    }
+
+    /// get returns the annotation `name` in the namespace.
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.annotations.get(&self.key(name))
+    }
+
+    /// remove removes the annotation `name` in the namespace, returning its value.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.annotations.remove(&self.key(name))
+    }
+
+    /// iter returns the annotations in the namespace with the namespace prefix stripped.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &String)> {
+        let prefix = format!("{}.", self.namespace);
+        self.annotations
+            .iter()
+            .filter_map(move |(key, value)| key.strip_prefix(&prefix).map(|name| (name, value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_namespace() {
+        assert!(validate_namespace("com.example").is_ok());
+        assert!(validate_namespace("io.k8s-tools.build").is_ok());
+        assert!(validate_namespace("example").is_err());
+        assert!(validate_namespace("Com.Example").is_err());
+        assert!(validate_namespace("com..example").is_err());
+        assert!(validate_namespace("org.opencontainers").is_err());
+        assert!(validate_namespace("org.opencontainers.custom").is_err());
+    }
+
+    #[test]
+    fn test_vendor() {
+        let mut annotations = HashMap::new();
+        let mut vendor = annotations.vendor("com.example").unwrap();
+        vendor.set("build", "42").unwrap();
+        assert!(vendor.set("", "42").is_err());
+        assert_eq!(vendor.get("build"), Some(&"42".to_string()));
+        assert_eq!(vendor.iter().count(), 1);
+        assert_eq!(
+            annotations.get("com.example.build"),
+            Some(&"42".to_string())
+        );
+    }
+
+    #[test]
+    fn test_lint_keys() {
+        let mut annotations = HashMap::new();
+        annotations.insert(ANNOTATION_CREATED.to_string(), "".to_string());
+        annotations.insert("com.example.build".to_string(), "".to_string());
+        annotations.insert(
+            "org.opencontainers.image.custom".to_string(),
+            "".to_string(),
+        );
+        annotations.insert("build".to_string(), "".to_string());
+        annotations.insert(ANNOTATION_NYDUS_BOOTSTRAP.to_string(), "true".to_string());
+        let keys = lint_keys(&annotations)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["build", "org.opencontainers.image.custom"]);
+    }
+}
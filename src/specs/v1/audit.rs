@@ -0,0 +1,126 @@
+use super::annotations::validate_namespace;
+use crate::clock::Clock;
+use crate::error::Error;
+use std::collections::HashMap;
+
+/// AuditEntry records a single modification of a document.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// Who is the identity of the person or system which made the change.
+    #[serde(rename = "who")]
+    pub who: String,
+
+    /// When is the date and time at which the change was made.
+    #[serde(rename = "when")]
+    pub when: chrono::DateTime<chrono::Utc>,
+
+    /// Tool is the name and version of the tool which made the change.
+    #[serde(rename = "tool")]
+    pub tool: String,
+
+    /// Action optionally describes the change.
+    #[serde(rename = "action", skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+}
+
+impl AuditEntry {
+    /// new returns an entry for a change made now, as told by clock.
+    pub fn new(who: &str, tool: &str, clock: &dyn Clock) -> Self {
+        AuditEntry {
+            who: who.to_string(),
+            when: clock.now(),
+            tool: tool.to_string(),
+            action: None,
+        }
+    }
+}
+
+/// AuditTrail appends audit entries to annotations under `<namespace>.audit.<n>`
+/// keys, with JSON encoded entries as values, and reads them back in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditTrail {
+    prefix: String,
+}
+
+impl AuditTrail {
+    /// new returns an audit trail stored in the given vendor namespace, e.g. `com.example`.
+    pub fn new(namespace: &str) -> Result<Self, Error> {
+        validate_namespace(namespace)?;
+        Ok(AuditTrail {
+            prefix: format!("{}.audit.", namespace),
+        })
+    }
+
+    /// append adds entry after the existing entries of the trail.
+    pub fn append(
+        &self,
+        annotations: &mut HashMap<String, String>,
+        entry: &AuditEntry,
+    ) -> Result<(), Error> {
+        let next = self
+            .indexed(annotations)
+            .map(|(index, _)| index + 1)
+            .max()
+            .unwrap_or_default();
+        annotations.insert(
+            format!("{}{}", self.prefix, next),
+            serde_json::to_string(entry)?,
+        );
+        Ok(())
+    }
+
+    /// history returns the entries of the trail, oldest first. Entries which
+    /// can't be decoded are skipped.
+    pub fn history(&self, annotations: &HashMap<String, String>) -> Vec<AuditEntry> {
+        let mut entries = self
+            .indexed(annotations)
+            .filter_map(|(index, value)| {
+                serde_json::from_str::<AuditEntry>(value)
+                    .ok()
+                    .map(|entry| (index, entry))
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(index, _)| *index);
+        entries.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    fn indexed<'a>(
+        &'a self,
+        annotations: &'a HashMap<String, String>,
+    ) -> impl Iterator<Item = (u64, &'a String)> + 'a {
+        annotations.iter().filter_map(move |(key, value)| {
+            key.strip_prefix(&self.prefix)
+                .and_then(|index| index.parse::<u64>().ok())
+                .map(|index| (index, value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_audit_trail() {
+        let trail = AuditTrail::new("com.example").unwrap();
+        let mut annotations = HashMap::new();
+        let clock = FixedClock::new(chrono::Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap());
+        for who in ["alice", "bob"] {
+            let entry = AuditEntry::new(who, "builder/1.0", &clock);
+            trail.append(&mut annotations, &entry).unwrap();
+            clock.advance(chrono::Duration::hours(1));
+        }
+        assert!(annotations.contains_key("com.example.audit.1"));
+        let history = trail.history(&annotations);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].who, "alice");
+        assert_eq!(history[1].who, "bob");
+        assert_eq!(
+            history[1].when,
+            chrono::Utc.with_ymd_and_hms(2024, 5, 1, 13, 0, 0).unwrap()
+        );
+        assert!(AuditTrail::new("org.opencontainers").is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use super::index::Index;
+use crate::error::Error;
+use crate::image_digest::algorithm::CANONICAL;
+use crate::image_digest::digester::Digester;
+use std::collections::{HashMap, HashSet};
+
+/// ANNOTATION_CONTINUATION is set on an index chunk to the digest of the
+/// chunk holding the next part of its manifests.
+pub const ANNOTATION_CONTINUATION: &str = "io.github.tosone.image-spec.continuation";
+
+/// Chunk is a serialized part of a split index, ready to be pushed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Chunk {
+    /// Digest is the canonical digest of the content.
+    pub digest: String,
+
+    /// Content is the JSON encoded index chunk.
+    pub content: Vec<u8>,
+}
+
+/// split_index splits an index, such as an oversized referrers index, into
+/// chunks of at most `max_manifests` manifests each. Every chunk but the last
+/// links to the next one through `ANNOTATION_CONTINUATION`. The first chunk
+/// keeps the artifact type, annotations and subject of the index and is the
+/// one to reference.
+pub fn split_index(index: &Index, max_manifests: usize) -> Result<Vec<Chunk>, Error> {
+    let mut parts = index
+        .manifests
+        .chunks(max_manifests.max(1))
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        parts.push(&[]);
+    }
+    let mut chunks: Vec<Chunk> = Vec::with_capacity(parts.len());
+    for (i, manifests) in parts.iter().enumerate().rev() {
+        let mut chunk = Index {
+            schema_version: index.schema_version,
+            media_type: index.media_type.clone(),
+            manifests: manifests.to_vec(),
+            ..Default::default()
+        };
+        if i == 0 {
+            chunk.artifact_type = index.artifact_type.clone();
+            chunk.subject = index.subject.clone();
+            chunk.annotations = index.annotations.clone();
+        }
+        if let Some(next) = chunks.last() {
+            chunk
+                .annotations
+                .get_or_insert_with(HashMap::new)
+                .insert(ANNOTATION_CONTINUATION.to_string(), next.digest.clone());
+        }
+        let content = serde_json::to_vec(&chunk)?;
+        let mut digester = Digester::new(CANONICAL)?;
+        digester.update(&content);
+        chunks.push(Chunk {
+            digest: digester.finalize().to_string(),
+            content,
+        });
+    }
+    chunks.reverse();
+    Ok(chunks)
+}
+
+/// join_index reassembles an index split by split_index, starting from its
+/// first chunk. `fetch` returns the chunk with the given digest. An index
+/// without continuation is returned unchanged.
+pub fn join_index<F>(mut head: Index, mut fetch: F) -> Result<Index, Error>
+where
+    F: FnMut(&str) -> Result<Index, Error>,
+{
+    let mut next = take_continuation(&mut head);
+    let mut visited = HashSet::new();
+    while let Some(digest) = next {
+        if !visited.insert(digest.clone()) {
+            return Err(Error::InvalidData(format!(
+                "index chunk {} is linked more than once",
+                digest
+            )));
+        }
+        let mut chunk = fetch(&digest)?;
+        next = take_continuation(&mut chunk);
+        head.manifests.append(&mut chunk.manifests);
+    }
+    Ok(head)
+}
+
+fn take_continuation(index: &mut Index) -> Option<String> {
+    let annotations = index.annotations.as_mut()?;
+    let continuation = annotations.remove(ANNOTATION_CONTINUATION);
+    if annotations.is_empty() {
+        index.annotations = None;
+    }
+    continuation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::specs::v1::descriptor::Descriptor;
+
+    #[test]
+    fn test_split_and_join() {
+        let index = Index {
+            schema_version: 2,
+            manifests: (0..5)
+                .map(|i| Descriptor {
+                    size: i,
+                    ..Default::default()
+                })
+                .collect(),
+            artifact_type: Some("application/vnd.example.sbom.v1+json".to_string()),
+            annotations: Some(HashMap::from([("a".to_string(), "b".to_string())])),
+            ..Default::default()
+        };
+        let chunks = split_index(&index, 2).unwrap();
+        assert_eq!(chunks.len(), 3);
+
+        let store = chunks
+            .iter()
+            .map(|chunk| {
+                let index: Index = serde_json::from_slice(&chunk.content).unwrap();
+                (chunk.digest.clone(), index)
+            })
+            .collect::<HashMap<_, _>>();
+        let head = store[&chunks[0].digest].clone();
+        assert_eq!(head.manifests.len(), 2);
+        let joined = join_index(head, |digest| Ok(store[digest].clone())).unwrap();
+        assert_eq!(joined, index);
+
+        assert_eq!(split_index(&Index::default(), 2).unwrap().len(), 1);
+    }
+}
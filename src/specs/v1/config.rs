@@ -1,5 +1,8 @@
+use crate::clock::{Clock, SystemClock};
+
+/// Nothing is the empty object used as value of the ExposedPorts and Volumes sets.
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
-pub struct Nothing;
+pub struct Nothing {}
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct ImageConfig {
@@ -7,11 +10,12 @@ pub struct ImageConfig {
     #[serde(rename = "User", skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
 
-    /// ExposedPorts a set of ports to expose from a container running this image.
+    /// ExposedPorts a set of ports to expose from a container running this image, keyed
+    /// by `port/protocol`, see ports and expose.
     #[serde(rename = "ExposedPorts", skip_serializing_if = "Option::is_none")]
     pub exposed_ports: Option<std::collections::HashMap<String, Nothing>>,
 
-    /// Env is a list of environment variables to be used in a container.
+    /// Env is a list of environment variables to be used in a container, see EnvVars.
     #[serde(rename = "Env", skip_serializing_if = "Option::is_none")]
     pub env: Option<Vec<String>>,
 
@@ -24,8 +28,9 @@ pub struct ImageConfig {
     pub cmd: Option<Vec<String>>,
 
     /// Volumes is a set of directories describing where the process is likely write data specific to a container instance.
+    /// Paths must be absolute, see VolumeSet.
     #[serde(rename = "Volumes", skip_serializing_if = "Option::is_none")]
-    pub volumes: Option<std::collections::HashMap<String, Nothing>>,
+    pub volumes: Option<super::volumes::VolumeSet>,
 
     /// WorkingDir sets the current working directory of the entrypoint process in the container.
     #[serde(rename = "WorkingDir", skip_serializing_if = "Option::is_none")]
@@ -38,6 +43,143 @@ pub struct ImageConfig {
     /// StopSignal contains the system call signal that will be sent to the container to exit.
     #[serde(rename = "StopSignal", skip_serializing_if = "Option::is_none")]
     pub stop_signal: Option<String>,
+
+    /// ArgsEscaped is set by Docker on Windows images when Cmd or Entrypoint
+    /// holds a single, already escaped command line. The specification
+    /// deprecates it, it is kept so configs re-serialize without loss.
+    #[serde(rename = "ArgsEscaped", skip_serializing_if = "Option::is_none")]
+    pub args_escaped: Option<bool>,
+
+    /// Healthcheck is the Docker extension describing how to check that a
+    /// container running this image is healthy.
+    #[serde(rename = "Healthcheck", skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<Healthcheck>,
+
+    /// OnBuild is the Docker extension listing the build instructions run
+    /// when the image is used as a base image. Docker writes `null` when there
+    /// are none, which is kept as `Some(None)` to re-serialize it unchanged.
+    #[serde(
+        rename = "OnBuild",
+        default,
+        deserialize_with = "deserialize_present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub on_build: Option<Option<Vec<String>>>,
+
+    /// Shell is the Docker extension holding the shell used by the shell form
+    /// of build instructions, e.g. `["/bin/sh", "-c"]`.
+    #[serde(rename = "Shell", skip_serializing_if = "Option::is_none")]
+    pub shell: Option<Vec<String>>,
+}
+
+/// Healthcheck is the Docker health check of a container. Durations are
+/// written in nanoseconds, as Docker does.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Healthcheck {
+    /// Test is the check to run: `["NONE"]` disables the check inherited from
+    /// the base image, `["CMD", args...]` runs a command and `["CMD-SHELL", command]`
+    /// runs a command with the shell.
+    #[serde(rename = "Test", skip_serializing_if = "Option::is_none")]
+    pub test: Option<Vec<String>>,
+
+    /// Interval is the time to wait between two checks.
+    #[serde(
+        rename = "Interval",
+        default,
+        with = "nanoseconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub interval: Option<std::time::Duration>,
+
+    /// Timeout is the time after which a check is considered hung.
+    #[serde(
+        rename = "Timeout",
+        default,
+        with = "nanoseconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<std::time::Duration>,
+
+    /// StartPeriod is the time the container is given to start before failed
+    /// checks count towards Retries.
+    #[serde(
+        rename = "StartPeriod",
+        default,
+        with = "nanoseconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_period: Option<std::time::Duration>,
+
+    /// Retries is the number of consecutive failures needed to consider the
+    /// container unhealthy.
+    #[serde(rename = "Retries", skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+}
+
+// deserialize_present wraps the value of a field that is present, even when
+// it is `null`, so that absent and `null` fields can be told apart.
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+// nanoseconds reads and writes durations as integer nanoseconds.
+mod nanoseconds {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => match u64::try_from(duration.as_nanos()) {
+                Ok(nanos) => serializer.serialize_u64(nanos),
+                Err(_) => Err(serde::ser::Error::custom("duration is too long")),
+            },
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_nanos))
+    }
+}
+
+/// DEFAULT_PATH is the PATH environment variable builders set for images built from scratch.
+pub const DEFAULT_PATH: &str = "PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+impl ImageConfig {
+    /// minimal returns the smallest config for an image built from scratch,
+    /// which only sets the default PATH.
+    pub fn minimal() -> Self {
+        ImageConfig {
+            env: Some(vec![DEFAULT_PATH.to_string()]),
+            ..Default::default()
+        }
+    }
+
+    /// effective_command returns the argv a runtime executes for this config.
+    /// `runtime_args` replace Cmd when non-empty and are appended to Entrypoint.
+    /// An empty Entrypoint, or one holding a single empty string, is treated as unset.
+    pub fn effective_command(&self, runtime_args: &[String]) -> Vec<String> {
+        let entrypoint = match &self.entrypoint {
+            Some(entrypoint) if entrypoint.len() == 1 && entrypoint[0].is_empty() => &[][..],
+            Some(entrypoint) => &entrypoint[..],
+            None => &[][..],
+        };
+        let args = if runtime_args.is_empty() {
+            self.cmd.as_deref().unwrap_or_default()
+        } else {
+            runtime_args
+        };
+        entrypoint.iter().chain(args.iter()).cloned().collect()
+    }
 }
 
 /// RootFS describes a layer content addresses
@@ -110,9 +252,10 @@ pub struct Image {
     #[serde(rename = "os.features", skip_serializing_if = "Option::is_none")]
     pub os_features: Option<Vec<String>>,
 
-    // Config defines the execution parameters which should be used as a base when running a container using the image.
-    // #[serde(rename = "config", skip_serializing_if = "Option::is_none")]
-    // pub config: Option<ImageConfig>,
+    /// Config defines the execution parameters which should be used as a base when running a container using the image.
+    #[serde(rename = "config", skip_serializing_if = "Option::is_none")]
+    pub config: Option<ImageConfig>,
+
     /// RootFS references the layer content addresses used by the image.
     #[serde(rename = "rootfs")]
     pub rootfs: RootFS,
@@ -121,3 +264,243 @@ pub struct Image {
     #[serde(rename = "history", skip_serializing_if = "Option::is_none")]
     pub history: Option<Vec<History>>,
 }
+
+impl Image {
+    /// scratch returns the config of an empty image for the given platform,
+    /// with a `layers` rootfs that has no diff_ids until layers are added.
+    pub fn scratch(platform: &super::descriptor::Platform) -> Self {
+        Image {
+            architecture: platform.architecture.clone(),
+            os: platform.os.clone(),
+            variant: platform.variant.clone(),
+            os_version: platform.os_version.clone(),
+            os_features: platform.os_features.clone(),
+            rootfs: RootFS {
+                type_: "layers".to_string(),
+                diff_ids: vec![],
+            },
+            ..Default::default()
+        }
+    }
+
+    /// age returns the time elapsed since the image was created, if the created
+    /// timestamp is set.
+    pub fn age(&self) -> Option<chrono::Duration> {
+        self.age_at(&SystemClock)
+    }
+
+    /// age_at returns the time elapsed since the image was created, as told by clock.
+    pub fn age_at(&self, clock: &dyn Clock) -> Option<chrono::Duration> {
+        self.created.map(|created| clock.now() - created)
+    }
+
+    /// human_age returns the image age in a human-readable form, e.g. `3 days`.
+    pub fn human_age(&self) -> Option<String> {
+        self.age().map(super::units::human_duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::specs::v1::descriptor::Platform;
+    use crate::specs::v1::validation::Validate;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_effective_command() {
+        let config = ImageConfig {
+            entrypoint: Some(strings(&["/bin/sh", "-c"])),
+            cmd: Some(strings(&["echo hello"])),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.effective_command(&[]),
+            strings(&["/bin/sh", "-c", "echo hello"])
+        );
+        assert_eq!(
+            config.effective_command(&strings(&["true"])),
+            strings(&["/bin/sh", "-c", "true"])
+        );
+
+        let config = ImageConfig {
+            entrypoint: Some(strings(&[""])),
+            cmd: Some(strings(&["/app"])),
+            ..Default::default()
+        };
+        assert_eq!(config.effective_command(&[]), strings(&["/app"]));
+        assert!(ImageConfig::default().effective_command(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_age_at() {
+        let clock = FixedClock::new(chrono::Utc::now());
+        let image = Image {
+            created: Some(clock.now()),
+            ..Default::default()
+        };
+        clock.advance(chrono::Duration::days(3));
+        assert_eq!(image.age_at(&clock), Some(chrono::Duration::days(3)));
+        assert_eq!(Image::default().age_at(&clock), None);
+    }
+
+    fn assert_roundtrip(json: &str) -> Image {
+        let image: Image = serde_json::from_str(json).unwrap();
+        let original: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_value(&image).unwrap(), original);
+        image
+    }
+
+    #[test]
+    fn test_roundtrip_docker() {
+        let image = assert_roundtrip(
+            r#"{
+                "architecture": "amd64",
+                "config": {
+                    "ExposedPorts": {"80/tcp": {}},
+                    "Env": [
+                        "PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin",
+                        "NGINX_VERSION=1.25.3"
+                    ],
+                    "Entrypoint": ["/docker-entrypoint.sh"],
+                    "Cmd": ["nginx", "-g", "daemon off;"],
+                    "Labels": {"maintainer": "NGINX Docker Maintainers <docker-maint@nginx.com>"},
+                    "StopSignal": "SIGQUIT"
+                },
+                "created": "2023-12-19T18:52:37.191435551Z",
+                "history": [
+                    {
+                        "created": "2023-12-19T04:38:08.474530734Z",
+                        "created_by": "/bin/sh -c #(nop) ADD file:9deb26e1dbc258df4 in / "
+                    },
+                    {
+                        "created": "2023-12-19T04:38:08.800939302Z",
+                        "created_by": "/bin/sh -c #(nop)  CMD [\"bash\"]",
+                        "empty_layer": true
+                    }
+                ],
+                "os": "linux",
+                "rootfs": {
+                    "type": "layers",
+                    "diff_ids": ["sha256:7292cf786aa89399bdb3e4c2e8e9e1a1d6bfc4d8e1e4a1e5ac5b6b0b30b2e4a5"]
+                }
+            }"#,
+        );
+        let config = image.config.unwrap();
+        assert_eq!(
+            config.effective_command(&[]),
+            strings(&["/docker-entrypoint.sh", "nginx", "-g", "daemon off;"])
+        );
+        assert!(config.exposed_ports.unwrap().contains_key("80/tcp"));
+    }
+
+    #[test]
+    fn test_roundtrip_buildkit() {
+        let image = assert_roundtrip(
+            r#"{
+                "architecture": "arm64",
+                "variant": "v8",
+                "os": "linux",
+                "created": "1970-01-01T00:00:00Z",
+                "config": {
+                    "User": "65532:65532",
+                    "Env": ["PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin"],
+                    "Entrypoint": ["/app"],
+                    "WorkingDir": "/home/nonroot",
+                    "Volumes": {"/data": {}},
+                    "Labels": {"org.opencontainers.image.source": "https://github.com/example/app"}
+                },
+                "rootfs": {"type": "layers", "diff_ids": []},
+                "history": [
+                    {
+                        "created": "1970-01-01T00:00:00Z",
+                        "created_by": "ENTRYPOINT [\"/app\"]",
+                        "comment": "buildkit.dockerfile.v0",
+                        "empty_layer": true
+                    }
+                ]
+            }"#,
+        );
+        let config = image.config.unwrap();
+        assert_eq!(config.user.as_deref(), Some("65532:65532"));
+        assert!(config.volumes.unwrap().contains("/data"));
+
+        let json = serde_json::to_string(&Image::default()).unwrap();
+        assert!(!json.contains("config"));
+    }
+
+    #[test]
+    fn test_roundtrip_windows() {
+        let image = assert_roundtrip(
+            r#"{
+                "architecture": "amd64",
+                "os": "windows",
+                "os.version": "10.0.20348.2227",
+                "config": {
+                    "Env": ["PATH=C:\\Windows\\system32;C:\\Windows"],
+                    "Cmd": ["cmd /S /C powershell.exe"],
+                    "ArgsEscaped": true
+                },
+                "rootfs": {
+                    "type": "layers",
+                    "diff_ids": ["sha256:4c6df22b0a7ee9cfb3a0c9c2ec4ae0a2e1e4d7b3a1c3f41e76fc0cbd1a0a6bb2"]
+                }
+            }"#,
+        );
+        assert_eq!(image.config.unwrap().args_escaped, Some(true));
+    }
+
+    #[test]
+    fn test_roundtrip_docker_extensions() {
+        let image = assert_roundtrip(
+            r#"{
+                "architecture": "amd64",
+                "os": "linux",
+                "config": {
+                    "Cmd": ["postgres"],
+                    "Healthcheck": {
+                        "Test": ["CMD-SHELL", "pg_isready -U postgres"],
+                        "Interval": 30000000000,
+                        "Timeout": 5000000000,
+                        "StartPeriod": 10000000000,
+                        "Retries": 3
+                    },
+                    "OnBuild": null,
+                    "Shell": ["/bin/bash", "-o", "pipefail", "-c"]
+                },
+                "rootfs": {"type": "layers", "diff_ids": []}
+            }"#,
+        );
+        let config = image.config.unwrap();
+        let healthcheck = config.healthcheck.unwrap();
+        assert_eq!(
+            healthcheck.interval,
+            Some(std::time::Duration::from_secs(30))
+        );
+        assert_eq!(healthcheck.retries, Some(3));
+        assert_eq!(config.on_build, Some(None));
+
+        let config: ImageConfig = serde_json::from_str(r#"{"OnBuild":["RUN make"]}"#).unwrap();
+        assert_eq!(config.on_build, Some(Some(strings(&["RUN make"]))));
+        let config: ImageConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.on_build, None);
+        assert_eq!(serde_json::to_string(&config).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_scratch() {
+        let platform = Platform {
+            architecture: "arm64".to_string(),
+            os: "linux".to_string(),
+            variant: Some("v8".to_string()),
+            ..Default::default()
+        };
+        let image = Image::scratch(&platform);
+        assert_eq!(image.variant, Some("v8".to_string()));
+        assert!(image.validate().is_valid());
+    }
+}
@@ -1,4 +1,15 @@
+use super::validation::Validate;
+use crate::error::Error;
+use crate::image_digest::algorithm::CANONICAL;
+use crate::image_digest::digest::Digest;
+use crate::image_digest::digester::{Digester, TeeDigestWriter};
+use base64::prelude::{Engine, BASE64_STANDARD};
 use std::collections::HashMap;
+use std::io::Read;
+
+/// MAX_SIZE is the largest size a descriptor can declare, the specification
+/// defining size as a 64-bit signed integer.
+pub const MAX_SIZE: u64 = i64::MAX as u64;
 
 /// Descriptor describes the disposition of targeted content.
 /// This structure provides `application/vnd.oci.descriptor.v1+json` mediatype
@@ -9,13 +20,15 @@ pub struct Descriptor {
     #[serde(rename = "mediaType", skip_serializing_if = "Option::is_none")]
     pub media_type: Option<String>,
 
-    /// Digest is the digest of the targeted content.
+    /// Digest is the digest of the targeted content. It is validated when
+    /// the descriptor is deserialized.
     #[serde(rename = "digest", skip_serializing_if = "Option::is_none")]
-    pub digest: Option<String>,
+    pub digest: Option<Digest>,
 
-    /// Size specifies the size in bytes of the blob.
-    #[serde(rename = "size")]
-    pub size: i64,
+    /// Size specifies the size in bytes of the blob. Negative sizes and sizes
+    /// above MAX_SIZE are rejected when the descriptor is deserialized.
+    #[serde(rename = "size", deserialize_with = "deserialize_size")]
+    pub size: u64,
 
     /// URLs specifies a list of URLs from which this object MAY be downloaded
     #[serde(rename = "urls", skip_serializing_if = "Option::is_none")]
@@ -25,10 +38,240 @@ pub struct Descriptor {
     #[serde(rename = "annotations", skip_serializing_if = "Option::is_none")]
     pub annotations: Option<HashMap<String, String>>,
 
+    /// Data is the base64 encoded content of the targeted blob, embedded in
+    /// the descriptor, see embed and decode_data.
+    #[serde(rename = "data", skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+
     /// Platform describes the platform which the image in the manifest runs on.
     /// This should only be used when referring to a manifest.
     #[serde(rename = "platform", skip_serializing_if = "Option::is_none")]
     pub platform: Option<Platform>,
+
+    /// ArtifactType is the IANA media type of the artifact, when the
+    /// descriptor refers to an artifact manifest.
+    #[serde(rename = "artifactType", skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+}
+
+impl Descriptor {
+    /// builder returns a DescriptorBuilder for a descriptor of the given media type.
+    pub fn builder(media_type: &str) -> DescriptorBuilder {
+        DescriptorBuilder::new(media_type)
+    }
+
+    /// from_bytes returns a descriptor of content with the given media type,
+    /// its size and its digest using the canonical algorithm.
+    pub fn from_bytes(media_type: &str, content: &[u8]) -> Descriptor {
+        let mut digester = Digester::new(CANONICAL).expect("canonical algorithm is registered");
+        digester.update(content);
+        Descriptor {
+            media_type: Some(media_type.to_string()),
+            digest: Some(digester.finalize()),
+            size: content.len() as u64,
+            ..Default::default()
+        }
+    }
+
+    /// from_reader is like from_bytes for content read from reader until end
+    /// of file, without holding it in memory.
+    pub fn from_reader<R: Read>(media_type: &str, mut reader: R) -> Result<Descriptor, Error> {
+        let mut writer = TeeDigestWriter::new(std::io::sink(), CANONICAL)?;
+        std::io::copy(&mut reader, &mut writer)?;
+        Ok(writer.descriptor(media_type))
+    }
+
+    /// embed sets the data field to the base64 encoding of content. The digest
+    /// and size are left untouched, see from_bytes to compute them.
+    pub fn embed(&mut self, content: &[u8]) {
+        self.data = Some(BASE64_STANDARD.encode(content));
+    }
+
+    /// decode_data returns the decoded content of the data field, None if the
+    /// descriptor has no embedded data.
+    pub fn decode_data(&self) -> Result<Option<Vec<u8>>, Error> {
+        match &self.data {
+            Some(data) => match BASE64_STANDARD.decode(data) {
+                Ok(content) => Ok(Some(content)),
+                Err(err) => Err(Error::InvalidData(format!(
+                    "invalid descriptor data: {}",
+                    err
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// verify_data decodes the embedded data and checks it against the declared
+    /// size and digest like verify, returning the content. The error is
+    /// `Error::Verify`, with MissingData when the descriptor has no data and
+    /// InvalidData when it isn't valid base64.
+    pub fn verify_data(&self) -> Result<Vec<u8>, Error> {
+        let data = self.data.as_ref().ok_or(VerifyError::MissingData)?;
+        let content = BASE64_STANDARD
+            .decode(data)
+            .map_err(|err| VerifyError::InvalidData(err.to_string()))?;
+        self.verify(content.as_slice())?;
+        Ok(content)
+    }
+
+    /// human_size returns the size of the targeted content in a human-readable form, e.g. `2.75MB`.
+    pub fn human_size(&self) -> String {
+        super::units::human_size(self.size)
+    }
+
+    /// with_config_platform returns a copy of the manifest descriptor with its
+    /// platform replaced by the one declared by the manifest's config, fixing
+    /// missing or wrong platform fields.
+    pub fn with_config_platform(&self, config: &super::config::Image) -> Descriptor {
+        Descriptor {
+            platform: Some(super::platform::from_config(config)),
+            ..self.clone()
+        }
+    }
+
+    /// verify reads the content described by the descriptor from reader and
+    /// checks that it has the declared size and digest. Reading stops one byte
+    /// past the declared size, so an oversized blob isn't read entirely. The
+    /// error is `Error::Verify` telling which check failed.
+    pub fn verify<R: Read>(&self, reader: R) -> Result<(), Error> {
+        let digest = self.digest.as_ref().ok_or(VerifyError::MissingDigest)?;
+        if self.size > MAX_SIZE {
+            return Err(VerifyError::InvalidSize(self.size).into());
+        }
+        let expected = self.size;
+        let mut verifier = digest.verifier()?;
+        let read = std::io::copy(&mut reader.take(expected.saturating_add(1)), &mut verifier)?;
+        if read > expected {
+            return Err(VerifyError::SizeExceeded { expected }.into());
+        }
+        if read < expected {
+            return Err(VerifyError::SizeMismatch {
+                expected,
+                actual: read,
+            }
+            .into());
+        }
+        if !verifier.verified() {
+            return Err(VerifyError::DigestMismatch {
+                expected: digest.clone(),
+                actual: verifier.digest(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// DescriptorBuilder builds a Descriptor field by field and validates it in
+/// build, e.g.
+/// `DescriptorBuilder::new(MEDIA_TYPE_IMAGE_CONFIG).digest(digest).size(5).build()`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DescriptorBuilder {
+    descriptor: Descriptor,
+}
+
+impl DescriptorBuilder {
+    /// new returns a builder for a descriptor of the given media type.
+    pub fn new(media_type: &str) -> Self {
+        DescriptorBuilder {
+            descriptor: Descriptor {
+                media_type: Some(media_type.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// digest sets the digest of the targeted content.
+    pub fn digest(mut self, digest: Digest) -> Self {
+        self.descriptor.digest = Some(digest);
+        self
+    }
+
+    /// size sets the size in bytes of the targeted content.
+    pub fn size(mut self, size: u64) -> Self {
+        self.descriptor.size = size;
+        self
+    }
+
+    /// url adds a URL from which the content may be downloaded.
+    pub fn url(mut self, url: &str) -> Self {
+        self.descriptor
+            .urls
+            .get_or_insert_with(Vec::new)
+            .push(url.to_string());
+        self
+    }
+
+    /// data embeds content in the descriptor, see Descriptor::embed.
+    pub fn data(mut self, content: &[u8]) -> Self {
+        self.descriptor.embed(content);
+        self
+    }
+
+    /// platform sets the platform of the targeted manifest.
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.descriptor.platform = Some(platform);
+        self
+    }
+
+    /// artifact_type sets the type of the artifact the descriptor refers to.
+    pub fn artifact_type(mut self, artifact_type: &str) -> Self {
+        self.descriptor.artifact_type = Some(artifact_type.to_string());
+        self
+    }
+
+    /// annotation sets an annotation, replacing any previous value of key.
+    pub fn annotation(mut self, key: &str, value: &str) -> Self {
+        self.descriptor
+            .annotations
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// build returns the descriptor, or `Error::Validation` if it isn't valid,
+    /// e.g. because the digest is missing or the media type is malformed.
+    pub fn build(self) -> Result<Descriptor, Error> {
+        self.descriptor.validate().into_result()?;
+        Ok(self.descriptor)
+    }
+}
+
+// deserialize_size reads a size as a signed integer, as the specification
+// defines it, and rejects negative values.
+pub(crate) fn deserialize_size<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u64, D::Error> {
+    let size = <i64 as serde::Deserialize>::deserialize(deserializer)?;
+    u64::try_from(size)
+        .map_err(|_| serde::de::Error::custom(format!("size must not be negative, got {}", size)))
+}
+
+/// VerifyError tells which check of Descriptor::verify failed.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum VerifyError {
+    /// The descriptor has no digest to verify against.
+    #[error("descriptor has no digest")]
+    MissingDigest,
+    /// The descriptor has no embedded data to verify.
+    #[error("descriptor has no embedded data")]
+    MissingData,
+    /// The embedded data isn't valid base64.
+    #[error("invalid descriptor data: {0}")]
+    InvalidData(String),
+    /// The descriptor declares a size above MAX_SIZE.
+    #[error("invalid descriptor size {0}")]
+    InvalidSize(u64),
+    /// The content is longer than the declared size.
+    #[error("content exceeds declared size of {expected} bytes")]
+    SizeExceeded { expected: u64 },
+    /// The content is shorter than the declared size.
+    #[error("content has {actual} bytes, declared size is {expected}")]
+    SizeMismatch { expected: u64, actual: u64 },
+    /// The content doesn't match the declared digest.
+    #[error("content digest {actual} doesn't match declared digest {expected}")]
+    DigestMismatch { expected: Digest, actual: Digest },
 }
 
 /// Platform describes the platform which the image in the manifest runs on.
@@ -58,3 +301,203 @@ pub struct Platform {
     #[serde(rename = "variant", skip_serializing_if = "Option::is_none")]
     pub variant: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verify_error(descriptor: &Descriptor, content: &[u8]) -> VerifyError {
+        match descriptor.verify(content).unwrap_err() {
+            Error::Verify(err) => err,
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let descriptor = Descriptor {
+            digest: Some(
+                "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+                    .parse()
+                    .unwrap(),
+            ),
+            size: 5,
+            ..Default::default()
+        };
+        assert!(descriptor.verify(b"hello".as_ref()).is_ok());
+        assert_eq!(
+            verify_error(&descriptor, b"hello!"),
+            VerifyError::SizeExceeded { expected: 5 }
+        );
+        assert_eq!(
+            verify_error(&descriptor, b"hell"),
+            VerifyError::SizeMismatch {
+                expected: 5,
+                actual: 4
+            }
+        );
+        assert!(matches!(
+            verify_error(&descriptor, b"hullo"),
+            VerifyError::DigestMismatch { .. }
+        ));
+        assert_eq!(
+            verify_error(
+                &Descriptor {
+                    digest: None,
+                    ..descriptor.clone()
+                },
+                b"hello"
+            ),
+            VerifyError::MissingDigest
+        );
+        assert_eq!(
+            verify_error(
+                &Descriptor {
+                    size: MAX_SIZE + 1,
+                    ..descriptor
+                },
+                b""
+            ),
+            VerifyError::InvalidSize(MAX_SIZE + 1)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_digest() {
+        let descriptor: Descriptor = serde_json::from_str(
+            r#"{"digest":"sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824","size":5}"#,
+        )
+        .unwrap();
+        assert_eq!(descriptor.digest.unwrap().algorithm(), "sha256");
+        assert!(serde_json::from_str::<Descriptor>(r#"{"digest":"sha256:abc","size":5}"#).is_err());
+        assert!(serde_json::from_str::<Descriptor>(r#"{"digest":"2cf24dba","size":5}"#).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_size() {
+        let size = |json: &str| serde_json::from_str::<Descriptor>(json).map(|d| d.size);
+        assert_eq!(size(r#"{"size":5}"#).unwrap(), 5);
+        assert_eq!(size(r#"{"size":9223372036854775807}"#).unwrap(), MAX_SIZE);
+        let err = size(r#"{"size":-1}"#).unwrap_err();
+        assert!(err.to_string().contains("size must not be negative"));
+        assert!(size(r#"{"size":9223372036854775808}"#).is_err());
+    }
+
+    #[test]
+    fn test_builder() {
+        let digest: Digest =
+            "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+                .parse()
+                .unwrap();
+        let descriptor = Descriptor::builder("application/vnd.oci.image.manifest.v1+json")
+            .digest(digest.clone())
+            .size(5)
+            .platform(Platform {
+                architecture: "amd64".to_string(),
+                os: "linux".to_string(),
+                ..Default::default()
+            })
+            .annotation("org.opencontainers.image.ref.name", "latest")
+            .build()
+            .unwrap();
+        assert_eq!(descriptor.digest, Some(digest.clone()));
+        assert_eq!(descriptor.size, 5);
+        assert_eq!(descriptor.platform.unwrap().os, "linux");
+        assert_eq!(
+            descriptor.annotations.unwrap()["org.opencontainers.image.ref.name"],
+            "latest"
+        );
+
+        let err = DescriptorBuilder::new("application/json").size(5).build();
+        assert!(matches!(err, Err(Error::Validation(_))));
+        let err = DescriptorBuilder::new("not a media type")
+            .digest(digest)
+            .build();
+        assert!(matches!(err, Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let descriptor = Descriptor::from_bytes("text/plain", b"hello");
+        assert_eq!(descriptor.media_type.as_deref(), Some("text/plain"));
+        assert_eq!(
+            descriptor.digest.as_ref().unwrap().to_string(),
+            "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(descriptor.size, 5);
+        assert_eq!(
+            Descriptor::from_reader("text/plain", b"hello".as_ref()).unwrap(),
+            descriptor
+        );
+    }
+
+    #[test]
+    fn test_embed() {
+        let mut descriptor = Descriptor::from_bytes("application/vnd.oci.empty.v1+json", b"{}");
+        assert_eq!(descriptor.decode_data().unwrap(), None);
+        descriptor.embed(b"{}");
+        assert_eq!(descriptor.data.as_deref(), Some("e30="));
+        assert_eq!(descriptor.decode_data().unwrap(), Some(b"{}".to_vec()));
+        let json = serde_json::to_value(&descriptor).unwrap();
+        assert_eq!(json["data"], "e30=");
+
+        descriptor.data = Some("not base64!".to_string());
+        assert!(matches!(
+            descriptor.decode_data(),
+            Err(Error::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_data() {
+        let mut descriptor = Descriptor::from_bytes("application/vnd.oci.empty.v1+json", b"{}");
+        assert!(matches!(
+            descriptor.verify_data(),
+            Err(Error::Verify(VerifyError::MissingData))
+        ));
+        descriptor.embed(b"{}");
+        assert_eq!(descriptor.verify_data().unwrap(), b"{}");
+
+        descriptor.embed(b"[]");
+        assert!(matches!(
+            descriptor.verify_data(),
+            Err(Error::Verify(VerifyError::DigestMismatch { .. }))
+        ));
+        descriptor.embed(b"{ }");
+        assert!(matches!(
+            descriptor.verify_data(),
+            Err(Error::Verify(VerifyError::SizeExceeded { expected: 2 }))
+        ));
+        descriptor.data = Some("e30".to_string());
+        assert!(matches!(
+            descriptor.verify_data(),
+            Err(Error::Verify(VerifyError::InvalidData(_)))
+        ));
+    }
+
+    #[test]
+    fn test_artifact_type() {
+        let descriptor = Descriptor::builder("application/vnd.oci.image.manifest.v1+json")
+            .digest(
+                Descriptor::from_bytes("text/plain", b"hello")
+                    .digest
+                    .unwrap(),
+            )
+            .size(5)
+            .artifact_type("application/vnd.example.sbom.v1+json")
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&descriptor).unwrap();
+        assert_eq!(json["artifactType"], "application/vnd.example.sbom.v1+json");
+        assert_eq!(
+            serde_json::from_value::<Descriptor>(json).unwrap(),
+            descriptor
+        );
+
+        let err = Descriptor::builder("application/vnd.oci.image.manifest.v1+json")
+            .digest(descriptor.digest.clone().unwrap())
+            .artifact_type("sbom")
+            .build();
+        assert!(matches!(err, Err(Error::Validation(_))));
+    }
+}
@@ -0,0 +1,163 @@
+use super::config::ImageConfig;
+use crate::error::Error;
+
+/// EnvVars is the list of `KEY=value` environment variables of an image
+/// config. Entries keep their order; when a key is repeated, the last entry
+/// wins, as it does for runtimes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EnvVars(Vec<String>);
+
+impl EnvVars {
+    /// new returns an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// get returns the value of the last entry for key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .rev()
+            .map(|e| split(e))
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// set sets key to value, replacing the first entry for key and removing
+    /// the others, or appending an entry if key isn't set.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let entry = format!("{}={}", key, value);
+        match self.0.iter().position(|e| split(e).0 == key) {
+            Some(i) => {
+                self.0[i] = entry;
+                let rest = self.0.split_off(i + 1);
+                self.0
+                    .extend(rest.into_iter().filter(|e| split(e).0 != key));
+            }
+            None => self.0.push(entry),
+        }
+    }
+
+    /// remove removes every entry for key and reports whether there was any.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.0.len();
+        self.0.retain(|e| split(e).0 != key);
+        self.0.len() != len
+    }
+
+    /// merge sets the variables of other, in order, so that its values win
+    /// over the ones already set, e.g. to layer overrides over the
+    /// environment of a base image.
+    pub fn merge(&mut self, other: &EnvVars) {
+        for (key, value) in other.iter() {
+            self.set(key, value);
+        }
+    }
+
+    /// iter returns the key and value of each entry, in order. An entry
+    /// without `=` has an empty value.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|e| split(e))
+    }
+
+    /// validate checks that every entry has the `KEY=value` form, with a non
+    /// empty key.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self.0.iter().find(|e| !is_valid(e)) {
+            Some(e) => Err(Error::InvalidInput(format!(
+                "invalid environment variable {:?}, expected KEY=value",
+                e
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// is_valid reports whether entry has the `KEY=value` form, with a non empty key.
+pub fn is_valid(entry: &str) -> bool {
+    matches!(entry.split_once('='), Some((key, _)) if !key.is_empty())
+}
+
+// split splits an entry into its key and value.
+fn split(entry: &str) -> (&str, &str) {
+    entry.split_once('=').unwrap_or((entry, ""))
+}
+
+impl From<Vec<String>> for EnvVars {
+    fn from(env: Vec<String>) -> Self {
+        EnvVars(env)
+    }
+}
+
+impl From<EnvVars> for Vec<String> {
+    fn from(env: EnvVars) -> Self {
+        env.0
+    }
+}
+
+impl ImageConfig {
+    /// env_vars returns a copy of Env, see set_env_vars to write it back.
+    pub fn env_vars(&self) -> EnvVars {
+        EnvVars(self.env.clone().unwrap_or_default())
+    }
+
+    /// set_env_vars replaces Env with env.
+    pub fn set_env_vars(&mut self, env: EnvVars) {
+        self.env = Some(env.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(entries: &[&str]) -> EnvVars {
+        EnvVars(entries.iter().map(|e| e.to_string()).collect())
+    }
+
+    #[test]
+    fn test_get_and_set() {
+        let mut vars = env(&["PATH=/bin", "A=1", "B=2", "A=3"]);
+        assert_eq!(vars.get("A"), Some("3"));
+        assert_eq!(vars.get("C"), None);
+
+        vars.set("A", "4");
+        vars.set("C", "x=y");
+        assert_eq!(vars, env(&["PATH=/bin", "A=4", "B=2", "C=x=y"]));
+        assert_eq!(vars.get("C"), Some("x=y"));
+
+        assert!(vars.remove("B"));
+        assert!(!vars.remove("B"));
+        assert_eq!(vars.len(), 3);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut config = ImageConfig::minimal();
+        let mut vars = config.env_vars();
+        vars.merge(&env(&["PATH=/app/bin:/bin", "LANG=C.UTF-8"]));
+        config.set_env_vars(vars);
+        assert_eq!(
+            config.env,
+            Some(vec![
+                "PATH=/app/bin:/bin".to_string(),
+                "LANG=C.UTF-8".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(env(&["A=", "B=1"]).validate().is_ok());
+        assert!(env(&["A"]).validate().is_err());
+        assert!(env(&["=1"]).validate().is_err());
+    }
+}
@@ -1,20 +1,280 @@
+use super::descriptor::Descriptor;
+use super::platform::PlatformMatcher;
+use crate::error::Error;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
 /// Index references manifests for various platforms.
 /// This structure provides `application/vnd.oci.image.index.v1+json` mediatype when marshalled to JSON.
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Index {
-    // SchemaVersion is the image manifest schema that this image follows
-    #[serde(rename = "SchemaVersion")]
+    // schemaVersion is the image manifest schema that this image follows
+    #[serde(rename = "schemaVersion")]
     pub schema_version: isize,
 
     // MediaType specificies the type of this document data structure e.g. `application/vnd.oci.image.index.v1+json`
     #[serde(rename = "mediaType", skip_serializing_if = "Option::is_none")]
     pub media_type: Option<String>,
 
+    // ArtifactType is the IANA media type of the artifact this index describes.
+    #[serde(rename = "artifactType", skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+
     // Manifests references platform specific manifests.
     #[serde(rename = "manifests")]
     pub manifests: Vec<super::descriptor::Descriptor>,
 
+    // Subject is an optional link from the image index to another manifest, forming an association between the index and the other manifest.
+    #[serde(rename = "subject", skip_serializing_if = "Option::is_none")]
+    pub subject: Option<super::descriptor::Descriptor>,
+
     // Annotations contains arbitrary metadata for the image index.
     #[serde(rename = "annotations", skip_serializing_if = "Option::is_none")]
     pub annotations: Option<std::collections::HashMap<String, String>>,
 }
+
+impl Index {
+    /// single_platform returns an index holding only the given manifest, with
+    /// its platform taken from the manifest's config. It is meant for copying a
+    /// single platform out of an index whose platform data can't be trusted.
+    pub fn single_platform(
+        manifest: &super::descriptor::Descriptor,
+        config: &super::config::Image,
+    ) -> Index {
+        Index {
+            schema_version: 2,
+            media_type: Some(super::mediatype::MEDIA_TYPE_IMAGE_INDEX.to_string()),
+            manifests: vec![manifest.with_config_platform(config)],
+            ..Default::default()
+        }
+    }
+
+    /// select returns the manifest which best matches matcher, which can be a
+    /// Platform to use the DefaultMatcher rules. Manifests without a platform
+    /// are accepted for any platform but preferred least. Nested indexes are
+    /// skipped, see select_nested.
+    pub fn select<M: PlatformMatcher + ?Sized>(&self, matcher: &M) -> Option<&Descriptor> {
+        best(
+            matcher,
+            self.manifests
+                .iter()
+                .filter(|descriptor| !is_index(descriptor)),
+        )
+    }
+
+    /// select_nested is like select, also considering the manifests of the
+    /// nested indexes, which `fetch` returns. Each nested index is fetched once,
+    /// so cycles end.
+    pub fn select_nested<M, F>(
+        &self,
+        matcher: &M,
+        mut fetch: F,
+    ) -> Result<Option<Descriptor>, Error>
+    where
+        M: PlatformMatcher + ?Sized,
+        F: FnMut(&Descriptor) -> Result<Index, Error>,
+    {
+        let mut manifests = Vec::new();
+        collect_manifests(self, &mut fetch, &mut HashSet::new(), &mut manifests)?;
+        Ok(best(matcher, manifests.iter()).cloned())
+    }
+}
+
+// is_index reports whether the descriptor refers to an OCI index or a Docker
+// manifest list.
+fn is_index(descriptor: &Descriptor) -> bool {
+    matches!(
+        descriptor.media_type.as_deref(),
+        Some(super::mediatype::MEDIA_TYPE_IMAGE_INDEX)
+            | Some("application/vnd.docker.distribution.manifest.list.v2+json")
+    )
+}
+
+// best returns the most preferred of the manifests accepted by matcher.
+fn best<'a, M: PlatformMatcher + ?Sized>(
+    matcher: &M,
+    manifests: impl Iterator<Item = &'a Descriptor>,
+) -> Option<&'a Descriptor> {
+    manifests
+        .filter(|descriptor| match &descriptor.platform {
+            Some(platform) => matcher.matches(platform),
+            None => true,
+        })
+        .min_by(|a, b| match (&a.platform, &b.platform) {
+            (Some(a), Some(b)) => matcher.compare(a, b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+}
+
+fn collect_manifests<F>(
+    index: &Index,
+    fetch: &mut F,
+    visited: &mut HashSet<String>,
+    manifests: &mut Vec<Descriptor>,
+) -> Result<(), Error>
+where
+    F: FnMut(&Descriptor) -> Result<Index, Error>,
+{
+    for descriptor in &index.manifests {
+        if !is_index(descriptor) {
+            manifests.push(descriptor.clone());
+            continue;
+        }
+        // Only digests identify an index, so nested indexes without one are
+        // always fetched.
+        let unvisited = match &descriptor.digest {
+            Some(digest) => visited.insert(digest.to_string()),
+            None => true,
+        };
+        if unvisited {
+            collect_manifests(&fetch(descriptor)?, fetch, visited, manifests)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::specs::v1::config::Image;
+    use crate::specs::v1::descriptor::Platform;
+    use crate::specs::v1::mediatype::{MEDIA_TYPE_IMAGE_INDEX, MEDIA_TYPE_IMAGE_MANIFEST};
+    use crate::specs::v1::platform::OsMatcher;
+
+    #[test]
+    fn test_single_platform() {
+        let manifest = Descriptor {
+            digest: Some(format!("sha256:{}", "a".repeat(64)).parse().unwrap()),
+            ..Default::default()
+        };
+        let config = Image {
+            architecture: "aarch64".to_string(),
+            os: "linux".to_string(),
+            ..Default::default()
+        };
+        let index = Index::single_platform(&manifest, &config);
+        assert_eq!(index.manifests.len(), 1);
+        let platform = index.manifests[0].platform.as_ref().unwrap();
+        assert_eq!(platform.architecture, "arm64");
+        assert_eq!(platform.variant, None);
+        assert_eq!(index.manifests[0].digest, manifest.digest);
+    }
+
+    fn manifest(platform: Option<&str>, fill: &str) -> Descriptor {
+        Descriptor {
+            media_type: Some(MEDIA_TYPE_IMAGE_MANIFEST.to_string()),
+            digest: Some(format!("sha256:{}", fill.repeat(64)).parse().unwrap()),
+            platform: platform.map(|platform| platform.parse().unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let index = Index {
+            manifests: vec![
+                manifest(Some("linux/amd64"), "a"),
+                manifest(Some("linux/arm/v6"), "b"),
+                manifest(Some("linux/arm/v7"), "c"),
+                manifest(Some("linux/arm64/v8"), "d"),
+            ],
+            ..Default::default()
+        };
+        let select = |platform: &str| {
+            index
+                .select(&platform.parse::<Platform>().unwrap())
+                .map(|descriptor| descriptor.digest.as_ref().unwrap().short(1))
+        };
+        assert_eq!(select("linux/aarch64").as_deref(), Some("sha256:d…"));
+        assert_eq!(select("linux/arm").as_deref(), Some("sha256:c…"));
+        assert_eq!(select("linux/arm/v6").as_deref(), Some("sha256:b…"));
+        assert_eq!(select("linux/arm/v5"), None);
+        assert_eq!(select("windows/amd64"), None);
+        assert!(index.select(&OsMatcher::new("linux")).is_some());
+
+        let fallback = Index {
+            manifests: vec![manifest(None, "e")],
+            ..Default::default()
+        };
+        assert!(fallback
+            .select(&"linux/s390x".parse::<Platform>().unwrap())
+            .is_some());
+    }
+
+    #[test]
+    fn test_select_nested() {
+        let nested = Index {
+            manifests: vec![manifest(Some("linux/arm64"), "d")],
+            ..Default::default()
+        };
+        let index = Index {
+            manifests: vec![
+                manifest(Some("linux/amd64"), "a"),
+                Descriptor {
+                    media_type: Some(MEDIA_TYPE_IMAGE_INDEX.to_string()),
+                    ..manifest(None, "f")
+                },
+            ],
+            ..Default::default()
+        };
+        let arm64: Platform = "linux/arm64".parse().unwrap();
+        assert_eq!(index.select(&arm64), None);
+        let mut fetched = 0;
+        let selected = index
+            .select_nested(&arm64, |_| {
+                fetched += 1;
+                Ok(nested.clone())
+            })
+            .unwrap();
+        assert_eq!(selected, Some(nested.manifests[0].clone()));
+        assert_eq!(fetched, 1);
+
+        let nested_index = |url: &str| Descriptor {
+            media_type: Some(MEDIA_TYPE_IMAGE_INDEX.to_string()),
+            urls: Some(vec![url.to_string()]),
+            ..Default::default()
+        };
+        let index = Index {
+            manifests: vec![nested_index("amd64"), nested_index("arm64")],
+            ..Default::default()
+        };
+        let selected = index
+            .select_nested(&arm64, |descriptor| {
+                Ok(match descriptor.urls.as_ref().unwrap()[0].as_str() {
+                    "arm64" => nested.clone(),
+                    _ => Index {
+                        manifests: vec![manifest(Some("linux/amd64"), "a")],
+                        ..Default::default()
+                    },
+                })
+            })
+            .unwrap();
+        assert_eq!(selected, Some(nested.manifests[0].clone()));
+    }
+
+    #[test]
+    fn test_artifact_index() {
+        let subject = manifest(Some("linux/amd64"), "a");
+        let index = Index {
+            schema_version: 2,
+            media_type: Some(MEDIA_TYPE_IMAGE_INDEX.to_string()),
+            artifact_type: Some("application/vnd.example.signatures.v1+json".to_string()),
+            manifests: vec![manifest(None, "b")],
+            subject: Some(subject),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&index).unwrap();
+        assert_eq!(
+            json["artifactType"],
+            "application/vnd.example.signatures.v1+json"
+        );
+        assert_eq!(json["subject"]["size"], 0);
+        assert_eq!(serde_json::from_value::<Index>(json).unwrap(), index);
+
+        let json = serde_json::to_value(Index::default()).unwrap();
+        assert!(json.get("artifactType").is_none());
+        assert!(json.get("subject").is_none());
+    }
+}
@@ -1,3 +1,7 @@
+use crate::error::Error;
+use crate::vfs::Vfs;
+use std::path::Path;
+
 /// IMAGE_LAYOUT_FILE is the file name of oci image layout file
 pub const IMAGE_LAYOUT_FILE: &str = "oci-layout";
 
@@ -11,3 +15,44 @@ pub struct ImageLayout {
     #[serde(rename = "imageLayoutVersion")]
     pub version: String,
 }
+
+impl ImageLayout {
+    /// new returns the layout of the version implemented by this crate.
+    pub fn new() -> Self {
+        ImageLayout {
+            version: IMAGE_LAYOUT_VERSION.to_string(),
+        }
+    }
+
+    /// read reads the `oci-layout` file of the layout directory root.
+    pub fn read(vfs: &dyn Vfs, root: &Path) -> Result<Self, Error> {
+        Ok(serde_json::from_slice(
+            &vfs.read(&root.join(IMAGE_LAYOUT_FILE))?,
+        )?)
+    }
+
+    /// write writes the `oci-layout` file of the layout directory root.
+    pub fn write(&self, vfs: &dyn Vfs, root: &Path) -> Result<(), Error> {
+        vfs.write(&root.join(IMAGE_LAYOUT_FILE), &serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::MemoryFs;
+
+    #[test]
+    fn test_read_write() {
+        let fs = MemoryFs::new();
+        let root = Path::new("/layout");
+        assert!(ImageLayout::read(&fs, root).is_err());
+        ImageLayout::new().write(&fs, root).unwrap();
+        assert_eq!(
+            fs.read(&root.join(IMAGE_LAYOUT_FILE)).unwrap(),
+            br#"{"imageLayoutVersion":"1.0.0"}"#
+        );
+        assert_eq!(ImageLayout::read(&fs, root).unwrap(), ImageLayout::new());
+    }
+}
@@ -2,13 +2,18 @@
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Manifest {
     /// schema_version is the image manifest schema that this image follows
-    #[serde(rename = "SchemaVersion")]
+    #[serde(rename = "schemaVersion")]
     pub schema_version: isize,
 
     /// MediaType specificies the type of this document data structure e.g. `application/vnd.oci.image.manifest.v1+json`
     #[serde(rename = "mediaType", skip_serializing_if = "Option::is_none")]
     pub media_type: Option<String>,
 
+    /// ArtifactType is the IANA media type of the artifact this manifest
+    /// describes. It must be set when the config is the empty descriptor.
+    #[serde(rename = "artifactType", skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+
     /// Config references a configuration object for a container, by digest.
     /// The referenced configuration object is a JSON blob that the runtime uses to set up the container.
     #[serde(rename = "config")]
@@ -18,7 +23,78 @@ pub struct Manifest {
     #[serde(rename = "layers")]
     pub layers: Vec<super::descriptor::Descriptor>,
 
+    /// Subject is an optional link from the manifest to another manifest,
+    /// forming an association used by the referrers API.
+    #[serde(rename = "subject", skip_serializing_if = "Option::is_none")]
+    pub subject: Option<super::descriptor::Descriptor>,
+
     /// Annotations contains arbitrary metadata for the image manifest.
     #[serde(rename = "annotations", skip_serializing_if = "Option::is_none")]
     pub annotations: Option<std::collections::HashMap<String, String>>,
 }
+
+/// SpecFeatures reports which image-spec v1.1 features a document uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SpecFeatures {
+    /// ZstdLayers is set when any layer uses a zstd compressed media type.
+    pub zstd_layers: bool,
+
+    /// EmbeddedData is set when the config or any layer descriptor embeds its
+    /// content in the `data` field.
+    pub embedded_data: bool,
+
+    /// Subject is set when the manifest refers to another manifest.
+    pub subject: bool,
+
+    /// ArtifactType is set when the manifest declares an artifact type.
+    pub artifact_type: bool,
+}
+
+impl SpecFeatures {
+    /// requires_v1_1 reports whether any v1.1 feature is in use, i.e. the
+    /// document can't be handled by an implementation of image-spec v1.0.
+    pub fn requires_v1_1(&self) -> bool {
+        self.zstd_layers || self.embedded_data || self.subject || self.artifact_type
+    }
+}
+
+impl Manifest {
+    /// spec_features reports which image-spec v1.1 features the manifest uses.
+    pub fn spec_features(&self) -> SpecFeatures {
+        SpecFeatures {
+            zstd_layers: self.layers.iter().any(|layer| {
+                matches!(
+                    layer.media_type.as_deref(),
+                    Some(super::mediatype::MEDIA_TYPE_IMAGE_LAYER_ZSTD)
+                        | Some(super::mediatype::MEDIA_TYPE_IMAGE_LAYER_NON_DISTRIBUTABLE_ZSTD)
+                )
+            }),
+            embedded_data: std::iter::once(&self.config)
+                .chain(self.layers.iter())
+                .any(|descriptor| descriptor.data.is_some()),
+            subject: self.subject.is_some(),
+            artifact_type: self.artifact_type.is_some(),
+        }
+    }
+
+    /// effective_artifact_type returns the type of the artifact the manifest
+    /// describes: its artifactType or, when unset, the media type of its config.
+    pub fn effective_artifact_type(&self) -> Option<&str> {
+        self.artifact_type
+            .as_deref()
+            .or(self.config.media_type.as_deref())
+    }
+
+    /// total_size returns the combined size in bytes of the config and all
+    /// layers, saturating at `u64::MAX`.
+    pub fn total_size(&self) -> u64 {
+        self.layers.iter().fold(self.config.size, |total, layer| {
+            total.saturating_add(layer.size)
+        })
+    }
+
+    /// human_total_size returns the total size in a human-readable form, e.g. `2.75MB`.
+    pub fn human_total_size(&self) -> String {
+        super::units::human_size(self.total_size())
+    }
+}
@@ -40,3 +40,114 @@ pub const MEDIA_TYPE_IMAGE_LAYER_NON_DISTRIBUTABLE_ZSTD: &str =
 
 /// MEDIA_TYPE_IMAGE_CONFIG specifies the media type for the image configuration.
 pub const MEDIA_TYPE_IMAGE_CONFIG: &str = "application/vnd.oci.image.config.v1+json";
+
+/// MEDIA_TYPE_EMPTY_JSON specifies the media type for the empty JSON object
+/// `{}`, used as config of artifact manifests.
+pub const MEDIA_TYPE_EMPTY_JSON: &str = "application/vnd.oci.empty.v1+json";
+
+/// is_valid_media_type reports whether value is a media type of the form
+/// `type/subtype` as defined by RFC 6838, without parameters.
+pub fn is_valid_media_type(value: &str) -> bool {
+    fn is_restricted_name(name: &str) -> bool {
+        name.len() <= 127
+            && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    }
+    match value.split_once('/') {
+        Some((type_, subtype)) => is_restricted_name(type_) && is_restricted_name(subtype),
+        None => false,
+    }
+}
+
+/// MediaTypePolicy decides which media types a consumer accepts. Patterns
+/// match a media type exactly, or by prefix when they end with `*` (e.g.
+/// `application/vnd.oci.image.layer.v1.tar*`). Denied patterns take precedence
+/// over allowed ones, and an empty allow-list allows every media type.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MediaTypePolicy {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl MediaTypePolicy {
+    /// new returns a policy allowing every media type.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// allow adds a pattern to the allow-list.
+    pub fn allow(mut self, pattern: &str) -> Self {
+        self.allow.push(pattern.to_string());
+        self
+    }
+
+    /// deny adds a pattern to the deny-list.
+    pub fn deny(mut self, pattern: &str) -> Self {
+        self.deny.push(pattern.to_string());
+        self
+    }
+
+    /// allows reports whether the policy accepts media_type. Media type
+    /// parameters such as `; charset=utf-8` are ignored.
+    pub fn allows(&self, media_type: &str) -> bool {
+        let media_type = media_type.split(';').next().unwrap_or_default().trim();
+        let matches = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => media_type.starts_with(prefix),
+            None => media_type == pattern,
+        };
+        !self.deny.iter().any(matches) && (self.allow.is_empty() || self.allow.iter().any(matches))
+    }
+
+    /// check returns an error if the policy doesn't accept the descriptor's
+    /// media type. A descriptor without a media type is only accepted when the
+    /// allow-list is empty.
+    pub fn check(
+        &self,
+        descriptor: &super::descriptor::Descriptor,
+    ) -> Result<(), crate::error::Error> {
+        let allowed = match &descriptor.media_type {
+            Some(media_type) => self.allows(media_type),
+            None => self.allow.is_empty(),
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(crate::error::Error::MediaTypeNotAllowed(
+                descriptor
+                    .media_type
+                    .as_deref()
+                    .unwrap_or("<none>")
+                    .to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::specs::v1::descriptor::Descriptor;
+
+    #[test]
+    fn test_is_valid_media_type() {
+        assert!(is_valid_media_type(MEDIA_TYPE_IMAGE_MANIFEST));
+        assert!(!is_valid_media_type("application"));
+        assert!(!is_valid_media_type("application/json; charset=utf-8"));
+    }
+
+    #[test]
+    fn test_media_type_policy() {
+        let policy = MediaTypePolicy::new()
+            .allow(MEDIA_TYPE_IMAGE_MANIFEST)
+            .allow("application/vnd.oci.image.layer.v1.tar*")
+            .deny(MEDIA_TYPE_IMAGE_LAYER_ZSTD);
+        assert!(policy.allows(MEDIA_TYPE_IMAGE_MANIFEST));
+        assert!(policy.allows(MEDIA_TYPE_IMAGE_LAYER_GZIP));
+        assert!(!policy.allows(MEDIA_TYPE_IMAGE_LAYER_ZSTD));
+        assert!(!policy.allows(MEDIA_TYPE_IMAGE_INDEX));
+        assert!(policy.check(&Descriptor::default()).is_err());
+        assert!(MediaTypePolicy::new().allows("application/x-anything"));
+    }
+}
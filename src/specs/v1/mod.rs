@@ -1,7 +1,20 @@
+pub mod acceleration;
 pub mod annotations;
+pub mod audit;
+pub mod chunk;
 pub mod config;
 pub mod descriptor;
+pub mod env;
 pub mod index;
 pub mod layout;
 pub mod manifest;
 pub mod mediatype;
+pub mod parse;
+pub mod platform;
+pub mod ports;
+pub mod referrers;
+pub mod roundtrip;
+pub mod scan;
+pub mod units;
+pub mod validation;
+pub mod volumes;
@@ -0,0 +1,287 @@
+use super::mediatype::*;
+use crate::error::Error;
+
+/// Document is a JSON document parsed according to its declared media type.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Document {
+    Descriptor(super::descriptor::Descriptor),
+    Layout(super::layout::ImageLayout),
+    Manifest(super::manifest::Manifest),
+    Index(super::index::Index),
+    Config(super::config::Image),
+}
+
+/// parse_as parses `bytes` as the document type declared by `media_type`,
+/// typically taken from a Content-Type header or a descriptor. It fails if the
+/// embedded `mediaType` field or the structure of the document doesn't match
+/// the declared media type.
+pub fn parse_as(media_type: &str, bytes: &[u8]) -> Result<Document, Error> {
+    // Content-Type headers may carry parameters, e.g. `; charset=utf-8`.
+    let media_type = media_type.split(';').next().unwrap_or_default().trim();
+
+    let value: serde_json::Value = serde_json::from_slice(bytes)?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| invalid_data("document is not a JSON object".to_string()))?;
+
+    if let Some(embedded) = object.get("mediaType") {
+        match embedded.as_str() {
+            Some(embedded) if embedded == media_type => {}
+            Some(embedded) => {
+                return Err(invalid_data(format!(
+                    "declared media type {} does not match embedded media type {}",
+                    media_type, embedded
+                )));
+            }
+            None => return Err(invalid_data("mediaType is not a string".to_string())),
+        }
+    }
+
+    let document = match media_type {
+        MEDIA_TYPE_IMAGE_MANIFEST => {
+            if object.contains_key("manifests") {
+                return Err(invalid_data(format!(
+                    "document declared as {} has the structure of an index",
+                    media_type
+                )));
+            }
+            Document::Manifest(serde_json::from_value(value)?)
+        }
+        MEDIA_TYPE_IMAGE_INDEX => {
+            if object.contains_key("config") || object.contains_key("layers") {
+                return Err(invalid_data(format!(
+                    "document declared as {} has the structure of a manifest",
+                    media_type
+                )));
+            }
+            Document::Index(serde_json::from_value(value)?)
+        }
+        MEDIA_TYPE_IMAGE_CONFIG => Document::Config(serde_json::from_value(value)?),
+        MEDIA_TYPE_DESCRIPTOR => Document::Descriptor(serde_json::from_value(value)?),
+        MEDIA_TYPE_LAYOUT_HEADER => Document::Layout(serde_json::from_value(value)?),
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "unsupported media type {}",
+                media_type
+            )));
+        }
+    };
+    Ok(document)
+}
+
+/// detect_media_type returns the media type of a JSON document: its embedded
+/// `mediaType` field or, when that is absent, the type recognized from its
+/// structure.
+pub fn detect_media_type(bytes: &[u8]) -> Result<String, Error> {
+    let value: serde_json::Value = serde_json::from_slice(bytes)?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| invalid_data("document is not a JSON object".to_string()))?;
+    let media_type = match object
+        .get("mediaType")
+        .and_then(|media_type| media_type.as_str())
+    {
+        Some(media_type) => media_type,
+        None if object.contains_key("manifests") => MEDIA_TYPE_IMAGE_INDEX,
+        None if object.contains_key("layers") => MEDIA_TYPE_IMAGE_MANIFEST,
+        None if object.contains_key("rootfs") => MEDIA_TYPE_IMAGE_CONFIG,
+        None if object.contains_key("imageLayoutVersion") => MEDIA_TYPE_LAYOUT_HEADER,
+        None if object.contains_key("digest") => MEDIA_TYPE_DESCRIPTOR,
+        None => {
+            return Err(invalid_data(
+                "unable to recognize the document type".to_string(),
+            ))
+        }
+    };
+    Ok(media_type.to_string())
+}
+
+/// parse parses `bytes` as the document type named by its embedded
+/// `mediaType` field or, when that is absent, recognized from its structure.
+pub fn parse(bytes: &[u8]) -> Result<Document, Error> {
+    parse_as(&detect_media_type(bytes)?, bytes)
+}
+
+/// LEGACY_SCHEMA_VERSION is the spelling of `schemaVersion` written by
+/// versions of this crate before 0.2.
+const LEGACY_SCHEMA_VERSION: &str = "SchemaVersion";
+
+/// parse_lenient is parse for documents which may have been written by older
+/// versions of this crate, accepting `SchemaVersion` as well as the
+/// `schemaVersion` required by the specification.
+pub fn parse_lenient(bytes: &[u8]) -> Result<Document, Error> {
+    parse(&serde_json::to_vec(&lenient_value(bytes)?)?)
+}
+
+/// from_slice_lenient deserializes a manifest, an index or a Versioned,
+/// accepting either casing of `schemaVersion`, see parse_lenient.
+pub fn from_slice_lenient<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, Error> {
+    Ok(serde_json::from_value(lenient_value(bytes)?)?)
+}
+
+// lenient_value parses bytes, renaming a legacy `SchemaVersion` field unless
+// the document also has a `schemaVersion`.
+fn lenient_value(bytes: &[u8]) -> Result<serde_json::Value, Error> {
+    let mut value: serde_json::Value = serde_json::from_slice(bytes)?;
+    if let Some(object) = value.as_object_mut() {
+        if !object.contains_key("schemaVersion") {
+            if let Some(version) = object.remove(LEGACY_SCHEMA_VERSION) {
+                object.insert("schemaVersion".to_string(), version);
+            }
+        }
+    }
+    Ok(value)
+}
+
+/// Strictness controls how load_index handles descriptors without a media type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strictness {
+    /// Lenient infers missing media types from the referenced blobs.
+    Lenient,
+    /// Strict fails, listing the descriptors without a media type.
+    Strict,
+}
+
+/// load_index parses an `index.json`, which older tools wrote without
+/// `mediaType` on descriptors. In lenient mode, `fetch` returns the blob
+/// referenced by such a descriptor, and its detected media type is backfilled.
+/// In strict mode `fetch` isn't called and the index is rejected instead.
+/// Lenient mode also accepts the `SchemaVersion` spelling, see parse_lenient.
+pub fn load_index<F>(
+    bytes: &[u8],
+    strictness: Strictness,
+    mut fetch: F,
+) -> Result<super::index::Index, Error>
+where
+    F: FnMut(&super::descriptor::Descriptor) -> Result<Vec<u8>, Error>,
+{
+    let mut index: super::index::Index = match strictness {
+        Strictness::Lenient => from_slice_lenient(bytes)?,
+        Strictness::Strict => serde_json::from_slice(bytes)?,
+    };
+    let missing = index
+        .manifests
+        .iter()
+        .enumerate()
+        .filter(|(_, descriptor)| descriptor.media_type.is_none())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return Ok(index);
+    }
+    if strictness == Strictness::Strict {
+        let pointers = missing
+            .iter()
+            .map(|i| format!("/manifests/{}", i))
+            .collect::<Vec<_>>();
+        return Err(invalid_data(format!(
+            "descriptors without mediaType: {}",
+            pointers.join(", ")
+        )));
+    }
+    for i in missing {
+        let descriptor = &mut index.manifests[i];
+        descriptor.media_type = Some(detect_media_type(&fetch(descriptor)?)?);
+    }
+    Ok(index)
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::InvalidData(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_as_config() {
+        let config =
+            br#"{"architecture":"amd64","os":"linux","rootfs":{"type":"layers","diff_ids":[]}}"#;
+        let document = parse_as(
+            "application/vnd.oci.image.config.v1+json; charset=utf-8",
+            config,
+        );
+        assert!(matches!(document, Ok(Document::Config(_))));
+    }
+
+    #[test]
+    fn test_parse() {
+        let index = br#"{"schemaVersion":2,"manifests":[]}"#;
+        assert!(matches!(parse(index), Ok(Document::Index(_))));
+        let layout = br#"{"imageLayoutVersion":"1.0.0"}"#;
+        assert!(matches!(parse(layout), Ok(Document::Layout(_))));
+        assert!(parse(b"{}").is_err());
+    }
+
+    #[test]
+    fn test_load_index() {
+        let index = format!(
+            r#"{{"schemaVersion":2,"manifests":[{{"digest":"sha256:{}","size":2}},{{"mediaType":"application/vnd.oci.image.index.v1+json","digest":"sha256:{}","size":2}}]}}"#,
+            "a".repeat(64),
+            "b".repeat(64)
+        );
+        let index = index.as_bytes();
+        let fetch = |_: &super::super::descriptor::Descriptor| {
+            Ok(br#"{"config":{"size":0},"layers":[]}"#.to_vec())
+        };
+
+        let loaded = load_index(index, Strictness::Lenient, fetch).unwrap();
+        assert_eq!(
+            loaded.manifests[0].media_type.as_deref(),
+            Some(MEDIA_TYPE_IMAGE_MANIFEST)
+        );
+        assert_eq!(
+            loaded.manifests[1].media_type.as_deref(),
+            Some(MEDIA_TYPE_IMAGE_INDEX)
+        );
+
+        let err = load_index(index, Strictness::Strict, fetch).unwrap_err();
+        assert!(err.to_string().contains("/manifests/0"));
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let legacy = format!(
+            r#"{{"SchemaVersion":2,"config":{{"digest":"sha256:{}","size":2}},"layers":[]}}"#,
+            "a".repeat(64)
+        );
+        assert!(parse(legacy.as_bytes()).is_err());
+        match parse_lenient(legacy.as_bytes()).unwrap() {
+            Document::Manifest(manifest) => {
+                assert_eq!(manifest.schema_version, 2);
+                let json = serde_json::to_string(&manifest).unwrap();
+                assert!(json.starts_with(r#"{"schemaVersion":2,"#));
+            }
+            document => panic!("unexpected document {:?}", document),
+        }
+
+        let versioned: crate::specs::versioned::Versioned =
+            from_slice_lenient(br#"{"SchemaVersion":2}"#).unwrap();
+        assert_eq!(versioned.schema_version, 2);
+        let index: super::super::index::Index =
+            from_slice_lenient(br#"{"schemaVersion":2,"manifests":[]}"#).unwrap();
+        assert_eq!(index.schema_version, 2);
+
+        let legacy = br#"{"SchemaVersion":2,"manifests":[]}"#;
+        let fetch =
+            |_: &super::super::descriptor::Descriptor| -> Result<Vec<u8>, Error> { unreachable!() };
+        assert!(load_index(legacy, Strictness::Lenient, fetch).is_ok());
+        assert!(load_index(legacy, Strictness::Strict, fetch).is_err());
+    }
+
+    #[test]
+    fn test_parse_as_media_type_mismatch() {
+        let index = br#"{"mediaType":"application/vnd.oci.image.index.v1+json","manifests":[]}"#;
+        assert!(parse_as(MEDIA_TYPE_IMAGE_MANIFEST, index).is_err());
+    }
+
+    #[test]
+    fn test_parse_as_structure_mismatch() {
+        let index = br#"{"manifests":[]}"#;
+        assert!(parse_as(MEDIA_TYPE_IMAGE_MANIFEST, index).is_err());
+        let manifest = br#"{"config":{"size":0},"layers":[]}"#;
+        assert!(parse_as(MEDIA_TYPE_IMAGE_INDEX, manifest).is_err());
+    }
+}
@@ -0,0 +1,648 @@
+use super::descriptor::Platform;
+use crate::error::Error;
+use std::cmp::Ordering;
+
+/// default_variant returns the variant assumed for `arch` when none is given,
+/// e.g. `v7` for `arm` and `v8` for `arm64`.
+pub fn default_variant(arch: &str) -> Option<&'static str> {
+    match arch {
+        "arm" => Some("v7"),
+        "arm64" => Some("v8"),
+        _ => None,
+    }
+}
+
+// default_arm_variant returns the default variant of arch, which must have one.
+fn default_arm_variant(arch: &str) -> String {
+    default_variant(arch).unwrap_or_default().to_string()
+}
+
+/// normalize_os returns the normalized form of an operating system name,
+/// e.g. `macOS` becomes `darwin`.
+pub fn normalize_os(os: &str) -> String {
+    let os = os.to_lowercase();
+    match os.as_str() {
+        "macos" => "darwin".to_string(),
+        _ => os,
+    }
+}
+
+/// normalize_arch returns the normalized architecture and variant, e.g.
+/// `(x86_64, "")` becomes `(amd64, "")` and `(armhf, "")` becomes `(arm, v7)`.
+/// The default `v8` variant of `arm64` normalizes to an empty variant while the
+/// default `v7` variant of `arm` is made explicit, see default_variant.
+pub fn normalize_arch(arch: &str, variant: &str) -> (String, String) {
+    let arch = arch.to_lowercase();
+    let variant = variant.to_lowercase();
+    let numeric = variant.starts_with(|c: char| c.is_ascii_digit());
+    match arch.as_str() {
+        "i386" => ("386".to_string(), String::new()),
+        "x86_64" | "x86-64" | "amd64" => match variant.as_str() {
+            "v1" => ("amd64".to_string(), String::new()),
+            _ => ("amd64".to_string(), variant),
+        },
+        "aarch64" | "arm64" => {
+            let variant = match variant.as_str() {
+                "8.0" | "v8.0" => "v8".to_string(),
+                "9" | "9.0" | "v9.0" => "v9".to_string(),
+                _ if numeric => format!("v{}", variant),
+                _ => variant,
+            };
+            if Some(variant.as_str()) == default_variant("arm64") {
+                ("arm64".to_string(), String::new())
+            } else {
+                ("arm64".to_string(), variant)
+            }
+        }
+        "armhf" => ("arm".to_string(), default_arm_variant("arm")),
+        "armel" => ("arm".to_string(), "v6".to_string()),
+        "arm" => match variant.as_str() {
+            "" => ("arm".to_string(), default_arm_variant("arm")),
+            _ if numeric => ("arm".to_string(), format!("v{}", variant)),
+            _ => ("arm".to_string(), variant),
+        },
+        _ => (arch, variant),
+    }
+}
+
+// cpuinfo_variant returns the arm variant number from the `CPU architecture`
+// line of `/proc/cpuinfo`, which some kernels report as `AArch64`.
+fn cpuinfo_variant(cpuinfo: &str) -> Option<String> {
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if key.trim() != "CPU architecture" {
+            return None;
+        }
+        match value.trim() {
+            "AArch64" => Some("8".to_string()),
+            value => Some(value.to_string()),
+        }
+    })
+}
+
+/// from_config returns the normalized platform declared by an image config.
+pub fn from_config(config: &super::config::Image) -> Platform {
+    Platform {
+        architecture: config.architecture.clone(),
+        os: config.os.clone(),
+        os_version: config.os_version.clone(),
+        os_features: config.os_features.clone(),
+        variant: config.variant.clone(),
+    }
+    .normalize()
+}
+
+impl Platform {
+    /// normalize returns the platform with its os and architecture normalized
+    /// following the containerd conventions, see normalize_os and
+    /// normalize_arch, so that platforms can be compared with `==`, e.g.
+    /// `Linux/x86_64` and `linux/amd64` normalize to the same platform.
+    pub fn normalize(&self) -> Platform {
+        let (architecture, variant) = normalize_arch(
+            &self.architecture,
+            self.variant.as_deref().unwrap_or_default(),
+        );
+        Platform {
+            architecture,
+            os: normalize_os(&self.os),
+            variant: Some(variant).filter(|variant| !variant.is_empty()),
+            ..self.clone()
+        }
+    }
+
+    /// host returns the normalized platform of the running process. The
+    /// variant of arm hosts is read from `/proc/cpuinfo` on Linux, and
+    /// otherwise defaults as in normalize_arch.
+    pub fn host() -> Platform {
+        let architecture = match std::env::consts::ARCH {
+            "x86" => "386",
+            "powerpc64" if cfg!(target_endian = "little") => "ppc64le",
+            "powerpc64" => "ppc64",
+            "mips64" if cfg!(target_endian = "little") => "mips64le",
+            "loongarch64" => "loong64",
+            arch => arch,
+        };
+        let variant = match architecture {
+            "arm" | "aarch64" => std::fs::read_to_string("/proc/cpuinfo")
+                .ok()
+                .and_then(|cpuinfo| cpuinfo_variant(&cpuinfo)),
+            _ => None,
+        };
+        Platform {
+            architecture: architecture.to_string(),
+            os: std::env::consts::OS.to_string(),
+            variant,
+            ..Default::default()
+        }
+        .normalize()
+    }
+}
+
+impl std::fmt::Display for Platform {
+    /// fmt writes the platform as `os/architecture`, followed by `/variant`
+    /// when the variant is set, e.g. `linux/arm/v7`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.os, self.architecture)?;
+        match self.variant.as_deref() {
+            Some(variant) if !variant.is_empty() => write!(f, "/{}", variant),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::str::FromStr for Platform {
+    type Err = Error;
+
+    /// from_str parses a platform in the `os/architecture[/variant]` form,
+    /// e.g. `linux/arm/v7`. The values are kept as given, see normalize_os and
+    /// normalize_arch.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split('/').collect::<Vec<_>>();
+        if !(2..=3).contains(&parts.len()) || parts.iter().any(|part| part.is_empty()) {
+            return Err(Error::InvalidInput(format!(
+                "invalid platform {:?}, expected os/architecture[/variant]",
+                s
+            )));
+        }
+        Ok(Platform {
+            os: parts[0].to_string(),
+            architecture: parts[1].to_string(),
+            variant: parts.get(2).map(|variant| variant.to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+/// PlatformMatcher decides which platforms are acceptable, e.g. when
+/// selecting a manifest from an index, and which one is preferred.
+pub trait PlatformMatcher {
+    /// matches reports whether platform is acceptable.
+    fn matches(&self, platform: &Platform) -> bool;
+
+    /// compare orders two platforms by preference, `Ordering::Less` meaning
+    /// that a is preferred over b. All platforms are equally preferred by
+    /// default.
+    fn compare(&self, _a: &Platform, _b: &Platform) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+// same reports whether two normalized platforms have the same os,
+// architecture and variant.
+fn same(a: &Platform, b: &Platform) -> bool {
+    a.os == b.os && a.architecture == b.architecture && a.variant == b.variant
+}
+
+/// ExactMatcher matches the platforms which normalize to the same os,
+/// architecture and variant as its platform.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExactMatcher {
+    platform: Platform,
+}
+
+impl ExactMatcher {
+    /// new returns a matcher for platform.
+    pub fn new(platform: &Platform) -> Self {
+        ExactMatcher {
+            platform: platform.normalize(),
+        }
+    }
+}
+
+impl PlatformMatcher for ExactMatcher {
+    fn matches(&self, platform: &Platform) -> bool {
+        same(&self.platform, &platform.normalize())
+    }
+}
+
+/// OsMatcher matches the platforms of an operating system, whatever their
+/// architecture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OsMatcher {
+    os: String,
+}
+
+impl OsMatcher {
+    /// new returns a matcher for os.
+    pub fn new(os: &str) -> Self {
+        OsMatcher {
+            os: normalize_os(os),
+        }
+    }
+}
+
+impl PlatformMatcher for OsMatcher {
+    fn matches(&self, platform: &Platform) -> bool {
+        normalize_os(&platform.os) == self.os
+    }
+}
+
+/// DefaultMatcher matches its platform and the platforms which can run on it,
+/// following the containerd rules: lower variants of `amd64` and `arm`, `386`
+/// on `amd64`, and `arm` up to the same variant on `arm64`. Platforms closer
+/// to its own are preferred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultMatcher {
+    compatible: Vec<Platform>,
+}
+
+impl DefaultMatcher {
+    /// new returns a matcher for platform.
+    pub fn new(platform: &Platform) -> Self {
+        DefaultMatcher {
+            compatible: compatible_platforms(&platform.normalize()),
+        }
+    }
+
+    // rank returns the position of platform in the compatible platforms.
+    fn rank(&self, platform: &Platform) -> Option<usize> {
+        let platform = platform.normalize();
+        self.compatible
+            .iter()
+            .position(|compatible| same(compatible, &platform))
+    }
+}
+
+impl PlatformMatcher for DefaultMatcher {
+    fn matches(&self, platform: &Platform) -> bool {
+        self.rank(platform).is_some()
+    }
+
+    fn compare(&self, a: &Platform, b: &Platform) -> Ordering {
+        let rank = |platform| self.rank(platform).unwrap_or(usize::MAX);
+        rank(a).cmp(&rank(b))
+    }
+}
+
+/// A platform matches like a DefaultMatcher for it.
+impl PlatformMatcher for Platform {
+    fn matches(&self, platform: &Platform) -> bool {
+        DefaultMatcher::new(self).matches(platform)
+    }
+
+    fn compare(&self, a: &Platform, b: &Platform) -> Ordering {
+        DefaultMatcher::new(self).compare(a, b)
+    }
+}
+
+/// WINDOWS_LTSC2022_BUILD is the build number of Windows Server 2022, the
+/// first release whose hosts also run containers of older builds, back to
+/// this one.
+pub const WINDOWS_LTSC2022_BUILD: u32 = 20348;
+
+/// WindowsVersion is a Windows `os.version` such as `10.0.17763.1040`:
+/// major, minor and build numbers, and an optional revision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct WindowsVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub build: u32,
+    pub revision: u32,
+}
+
+impl WindowsVersion {
+    /// runs_on reports whether a container of this version can run on host,
+    /// following the containerd policy: major and minor versions must be equal
+    /// and, before Windows Server 2022, builds as well. From Windows Server
+    /// 2022 on, hosts run containers from that release up to their own build.
+    /// Revisions don't matter.
+    pub fn runs_on(&self, host: &WindowsVersion) -> bool {
+        if (self.major, self.minor) != (host.major, host.minor) {
+            return false;
+        }
+        if host.build < WINDOWS_LTSC2022_BUILD {
+            return self.build == host.build;
+        }
+        (WINDOWS_LTSC2022_BUILD..=host.build).contains(&self.build)
+    }
+}
+
+impl std::fmt::Display for WindowsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.build, self.revision
+        )
+    }
+}
+
+impl std::str::FromStr for WindowsVersion {
+    type Err = Error;
+
+    /// from_str parses a `major.minor.build[.revision]` version.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidInput(format!("invalid Windows version {:?}", s));
+        let parts = s
+            .split('.')
+            .map(|part| part.parse::<u32>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match parts[..] {
+            [major, minor, build] => Ok(WindowsVersion {
+                major,
+                minor,
+                build,
+                revision: 0,
+            }),
+            [major, minor, build, revision] => Ok(WindowsVersion {
+                major,
+                minor,
+                build,
+                revision,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// WindowsMatcher is a DefaultMatcher which, for a Windows platform with an
+/// `os.version`, also requires candidates to run on that version, see
+/// WindowsVersion::runs_on. Candidates without `os.version` match, and among
+/// matching candidates the most recent version is preferred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowsMatcher {
+    matcher: DefaultMatcher,
+    os_version: Option<WindowsVersion>,
+}
+
+impl WindowsMatcher {
+    /// new returns a matcher for platform. An `os.version` which can't be
+    /// parsed is ignored.
+    pub fn new(platform: &Platform) -> Self {
+        let platform = platform.normalize();
+        WindowsMatcher {
+            matcher: DefaultMatcher::new(&platform),
+            os_version: match platform.os.as_str() {
+                "windows" => platform
+                    .os_version
+                    .as_deref()
+                    .and_then(|version| version.parse().ok()),
+                _ => None,
+            },
+        }
+    }
+}
+
+// windows_version returns the parsed `os.version` of platform, if any.
+fn windows_version(platform: &Platform) -> Option<Result<WindowsVersion, Error>> {
+    platform.os_version.as_deref().map(str::parse)
+}
+
+impl PlatformMatcher for WindowsMatcher {
+    fn matches(&self, platform: &Platform) -> bool {
+        if !self.matcher.matches(platform) {
+            return false;
+        }
+        match (&self.os_version, windows_version(platform)) {
+            (Some(host), Some(Ok(version))) => version.runs_on(host),
+            (Some(_), Some(Err(_))) => false,
+            _ => true,
+        }
+    }
+
+    fn compare(&self, a: &Platform, b: &Platform) -> Ordering {
+        let version = |platform| windows_version(platform).and_then(Result::ok);
+        self.matcher
+            .compare(a, b)
+            .then_with(|| version(b).cmp(&version(a)))
+    }
+}
+
+// compatible_platforms returns the normalized platforms which can run on
+// platform, from the most to the least preferred.
+fn compatible_platforms(platform: &Platform) -> Vec<Platform> {
+    let with = |architecture: &str, variant: String| {
+        Platform {
+            architecture: architecture.to_string(),
+            variant: Some(variant),
+            ..platform.clone()
+        }
+        .normalize()
+    };
+    let version = |variant: &str| {
+        variant
+            .strip_prefix('v')
+            .and_then(|v| v.parse::<u32>().ok())
+    };
+    let variant = platform.variant.clone().unwrap_or_default();
+    let mut platforms = vec![platform.clone()];
+    match platform.architecture.as_str() {
+        "amd64" => {
+            if let Some(version) = version(&variant) {
+                platforms.extend((1..version).rev().map(|v| with("amd64", format!("v{}", v))));
+            }
+            platforms.push(with("386", String::new()));
+        }
+        "arm" => {
+            if let Some(version) = version(&variant) {
+                platforms.extend((5..version).rev().map(|v| with("arm", format!("v{}", v))));
+            }
+        }
+        "arm64" => {
+            let variant = if variant.is_empty() {
+                default_arm_variant("arm64")
+            } else {
+                variant
+            };
+            platforms.extend(compatible_platforms(&with("arm", variant)));
+        }
+        _ => {}
+    }
+    platforms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_normalize_arch() {
+        let cases = [
+            (("i386", ""), ("386", "")),
+            (("x86_64", ""), ("amd64", "")),
+            (("x86-64", "v1"), ("amd64", "")),
+            (("amd64", "v3"), ("amd64", "v3")),
+            (("aarch64", ""), ("arm64", "")),
+            (("arm64", "v8"), ("arm64", "")),
+            (("arm64", "8"), ("arm64", "")),
+            (("arm64", "9"), ("arm64", "v9")),
+            (("arm64", "8.1"), ("arm64", "v8.1")),
+            (("arm64", "8.0"), ("arm64", "")),
+            (("armhf", ""), ("arm", "v7")),
+            (("armel", ""), ("arm", "v6")),
+            (("arm", ""), ("arm", "v7")),
+            (("arm", "6"), ("arm", "v6")),
+            (("arm", "v5"), ("arm", "v5")),
+            (("ppc64le", ""), ("ppc64le", "")),
+        ];
+        for ((arch, variant), (expected_arch, expected_variant)) in cases {
+            assert_eq!(
+                normalize_arch(arch, variant),
+                (expected_arch.to_string(), expected_variant.to_string()),
+                "normalize_arch({:?}, {:?})",
+                arch,
+                variant
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_os() {
+        assert_eq!(normalize_os("Linux"), "linux");
+        assert_eq!(normalize_os("macOS"), "darwin");
+    }
+
+    #[test]
+    fn test_default_variant() {
+        assert_eq!(default_variant("arm"), Some("v7"));
+        assert_eq!(default_variant("arm64"), Some("v8"));
+        assert_eq!(default_variant("amd64"), None);
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let platform: Platform = "linux/arm/v7".parse().unwrap();
+        assert_eq!(platform.os, "linux");
+        assert_eq!(platform.architecture, "arm");
+        assert_eq!(platform.variant.as_deref(), Some("v7"));
+        assert_eq!(platform.to_string(), "linux/arm/v7");
+
+        let platform: Platform = "windows/amd64".parse().unwrap();
+        assert_eq!(platform.variant, None);
+        assert_eq!(platform.to_string(), "windows/amd64");
+
+        for invalid in ["", "linux", "linux/", "/amd64", "linux/arm/v7/x"] {
+            assert!(invalid.parse::<Platform>().is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        let normalize = |s: &str| s.parse::<Platform>().unwrap().normalize().to_string();
+        assert_eq!(normalize("Linux/x86_64"), "linux/amd64");
+        assert_eq!(normalize("linux/aarch64"), "linux/arm64");
+        assert_eq!(normalize("linux/arm64/v8"), "linux/arm64");
+        assert_eq!(normalize("linux/armhf"), "linux/arm/v7");
+        assert_eq!(normalize("linux/arm"), "linux/arm/v7");
+        assert_eq!(normalize("macOS/arm64"), "darwin/arm64");
+        assert_eq!(
+            "linux/x86_64".parse::<Platform>().unwrap().normalize(),
+            "linux/amd64".parse::<Platform>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_host() {
+        let host = Platform::host();
+        assert_eq!(host, host.normalize());
+        assert_eq!(host.os, normalize_os(std::env::consts::OS));
+        assert!(!host.architecture.is_empty());
+
+        let cpuinfo = "processor\t: 0\nCPU architecture: 7\nCPU variant\t: 0x0\n";
+        assert_eq!(cpuinfo_variant(cpuinfo).as_deref(), Some("7"));
+        assert_eq!(
+            cpuinfo_variant("CPU architecture: AArch64").as_deref(),
+            Some("8")
+        );
+        assert_eq!(cpuinfo_variant("processor\t: 0"), None);
+    }
+
+    #[test]
+    fn test_matchers() {
+        let platform = |s: &str| s.parse::<Platform>().unwrap();
+
+        let exact = ExactMatcher::new(&platform("linux/arm64"));
+        assert!(exact.matches(&platform("linux/aarch64")));
+        assert!(exact.matches(&platform("linux/arm64/v8")));
+        assert!(!exact.matches(&platform("linux/arm/v7")));
+
+        let os = OsMatcher::new("Linux");
+        assert!(os.matches(&platform("linux/s390x")));
+        assert!(!os.matches(&platform("windows/amd64")));
+
+        let arm64 = DefaultMatcher::new(&platform("linux/arm64"));
+        for compatible in [
+            "linux/arm64",
+            "linux/arm/v8",
+            "linux/arm/v7",
+            "linux/arm/v5",
+        ] {
+            assert!(arm64.matches(&platform(compatible)), "{}", compatible);
+        }
+        assert!(!arm64.matches(&platform("linux/amd64")));
+        assert!(!arm64.matches(&platform("windows/arm64")));
+        assert_eq!(
+            arm64.compare(&platform("linux/arm64"), &platform("linux/arm/v7")),
+            Ordering::Less
+        );
+        assert_eq!(
+            arm64.compare(&platform("linux/arm/v6"), &platform("linux/arm/v7")),
+            Ordering::Greater
+        );
+
+        let armv6 = DefaultMatcher::new(&platform("linux/arm/v6"));
+        assert!(armv6.matches(&platform("linux/arm/v5")));
+        assert!(!armv6.matches(&platform("linux/arm/v7")));
+
+        let amd64 = DefaultMatcher::new(&platform("linux/amd64/v3"));
+        assert!(amd64.matches(&platform("linux/amd64")));
+        assert!(amd64.matches(&platform("linux/amd64/v2")));
+        assert!(amd64.matches(&platform("linux/386")));
+        assert!(!amd64.matches(&platform("linux/amd64/v4")));
+    }
+
+    #[test]
+    fn test_windows_version() {
+        let version: WindowsVersion = "10.0.17763.1040".parse().unwrap();
+        assert_eq!(
+            version,
+            WindowsVersion {
+                major: 10,
+                minor: 0,
+                build: 17763,
+                revision: 1040
+            }
+        );
+        assert_eq!(version.to_string(), "10.0.17763.1040");
+        assert_eq!("10.0.20348".parse::<WindowsVersion>().unwrap().revision, 0);
+        for invalid in ["", "10.0", "10.0.x", "10.0.1.2.3"] {
+            assert!(invalid.parse::<WindowsVersion>().is_err(), "{:?}", invalid);
+        }
+
+        let version = |s: &str| s.parse::<WindowsVersion>().unwrap();
+        let ltsc2019 = version("10.0.17763.1");
+        assert!(version("10.0.17763.5").runs_on(&ltsc2019));
+        assert!(!version("10.0.17134.1").runs_on(&ltsc2019));
+        let host = version("10.0.25398.1");
+        assert!(version("10.0.20348.100").runs_on(&host));
+        assert!(version("10.0.25398.1").runs_on(&host));
+        assert!(!version("10.0.17763.1").runs_on(&host));
+        assert!(!version("10.0.26100.1").runs_on(&host));
+        assert!(!version("6.3.25398.1").runs_on(&host));
+    }
+
+    #[test]
+    fn test_windows_matcher() {
+        let windows = |version: Option<&str>| Platform {
+            os_version: version.map(str::to_string),
+            ..Platform::from_str("windows/amd64").unwrap()
+        };
+        let matcher = WindowsMatcher::new(&windows(Some("10.0.17763.1040")));
+        assert!(matcher.matches(&windows(Some("10.0.17763.2000"))));
+        assert!(matcher.matches(&windows(None)));
+        assert!(!matcher.matches(&windows(Some("10.0.20348.1"))));
+        assert!(!matcher.matches(&windows(Some("latest"))));
+        assert!(!matcher.matches(&Platform::from_str("linux/amd64").unwrap()));
+        assert_eq!(
+            matcher.compare(
+                &windows(Some("10.0.17763.2000")),
+                &windows(Some("10.0.17763.1040"))
+            ),
+            Ordering::Less
+        );
+
+        let any = WindowsMatcher::new(&windows(None));
+        assert!(any.matches(&windows(Some("10.0.20348.1"))));
+    }
+}
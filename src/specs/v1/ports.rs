@@ -0,0 +1,146 @@
+use super::config::{ImageConfig, Nothing};
+use crate::error::Error;
+
+/// Protocol is the transport protocol of an exposed port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+    Sctp,
+}
+
+impl Protocol {
+    /// as_str returns the name of the protocol used in ExposedPorts keys, e.g. `tcp`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Udp => "udp",
+            Protocol::Sctp => "sctp",
+        }
+    }
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// PortProtocol is a key of ExposedPorts: a port number and its protocol,
+/// e.g. `8080/tcp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PortProtocol {
+    pub port: u16,
+    pub protocol: Protocol,
+}
+
+impl PortProtocol {
+    /// new returns the port with the given protocol.
+    pub fn new(port: u16, protocol: Protocol) -> Self {
+        PortProtocol { port, protocol }
+    }
+}
+
+impl std::fmt::Display for PortProtocol {
+    /// fmt writes the port in the `port/protocol` form, the protocol being
+    /// written even when it is the default `tcp`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.port, self.protocol)
+    }
+}
+
+impl std::str::FromStr for PortProtocol {
+    type Err = Error;
+
+    /// from_str parses a port in the `port[/protocol]` form, e.g. `53/udp`.
+    /// The protocol defaults to `tcp` and the port must be within 1-65535.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |reason: &str| Error::InvalidInput(format!("invalid port {:?}: {}", s, reason));
+        let (port, protocol) = s.split_once('/').unwrap_or((s, "tcp"));
+        let protocol = match protocol {
+            "tcp" => Protocol::Tcp,
+            "udp" => Protocol::Udp,
+            "sctp" => Protocol::Sctp,
+            _ => return Err(invalid("protocol must be tcp, udp or sctp")),
+        };
+        if port.is_empty() || !port.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid("port must be a number"));
+        }
+        match port.parse::<u16>() {
+            Ok(port) if port > 0 => Ok(PortProtocol::new(port, protocol)),
+            _ => Err(invalid("port must be within 1-65535")),
+        }
+    }
+}
+
+impl ImageConfig {
+    /// ports returns the exposed ports in ascending order. It fails on the
+    /// first key of ExposedPorts which isn't a valid port.
+    pub fn ports(&self) -> Result<Vec<PortProtocol>, Error> {
+        let mut ports = self
+            .exposed_ports
+            .iter()
+            .flatten()
+            .map(|(key, _)| key.parse())
+            .collect::<Result<Vec<PortProtocol>, Error>>()?;
+        ports.sort();
+        ports.dedup();
+        Ok(ports)
+    }
+
+    /// expose adds port to ExposedPorts, under its `port/protocol` key.
+    pub fn expose(&mut self, port: PortProtocol) {
+        self.exposed_ports
+            .get_or_insert_with(Default::default)
+            .insert(port.to_string(), Nothing {});
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let port: PortProtocol = "53/udp".parse().unwrap();
+        assert_eq!(port, PortProtocol::new(53, Protocol::Udp));
+        assert_eq!(port.to_string(), "53/udp");
+        let port: PortProtocol = "8080".parse().unwrap();
+        assert_eq!(port, PortProtocol::new(8080, Protocol::Tcp));
+        assert_eq!(port.to_string(), "8080/tcp");
+
+        for invalid in [
+            "", "0/tcp", "65536", "-1", "+80", "80/http", "http/tcp", "/tcp",
+        ] {
+            assert!(invalid.parse::<PortProtocol>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_ports() {
+        let mut config = ImageConfig::default();
+        assert!(config.ports().unwrap().is_empty());
+        config.expose(PortProtocol::new(443, Protocol::Tcp));
+        config.expose("53/udp".parse().unwrap());
+        config.expose("53/udp".parse().unwrap());
+        assert_eq!(
+            config.ports().unwrap(),
+            vec![
+                PortProtocol::new(53, Protocol::Udp),
+                PortProtocol::new(443, Protocol::Tcp)
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&config).unwrap()["ExposedPorts"]["53/udp"],
+            serde_json::json!({})
+        );
+
+        config
+            .exposed_ports
+            .as_mut()
+            .unwrap()
+            .insert("garbage".to_string(), Nothing {});
+        assert!(config.ports().is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use super::descriptor::Descriptor;
+use super::index::Index;
+use super::mediatype::MEDIA_TYPE_IMAGE_INDEX;
+use crate::error::Error;
+use std::collections::{HashSet, VecDeque};
+
+/// Referrer is a manifest or index found through the referrers of a subject.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Referrer {
+    /// Subject is the digest of the manifest or index referred to.
+    pub subject: String,
+
+    /// Descriptor describes the referring manifest or index.
+    pub descriptor: Descriptor,
+
+    /// Depth is 1 for referrers of the root subject, 2 for referrers of those, and so on.
+    pub depth: usize,
+}
+
+impl Referrer {
+    /// is_index reports whether the referrer is an image index, such as a
+    /// signature index, rather than a manifest.
+    pub fn is_index(&self) -> bool {
+        self.descriptor.media_type.as_deref() == Some(MEDIA_TYPE_IMAGE_INDEX)
+    }
+}
+
+/// discover returns the referrers of subject and, recursively, the referrers
+/// of those, up to `max_depth` levels. `fetch` returns the referrers index of a
+/// digest, as served by the distribution referrers API. Index referrers are
+/// reported as a whole and their own referrers are followed; the manifests
+/// they list are not expanded. Each digest is visited once, so cycles end.
+pub fn discover<F>(subject: &str, max_depth: usize, mut fetch: F) -> Result<Vec<Referrer>, Error>
+where
+    F: FnMut(&str) -> Result<Index, Error>,
+{
+    let mut referrers = Vec::new();
+    let mut visited = HashSet::from([subject.to_string()]);
+    let mut queue = VecDeque::from([(subject.to_string(), 1)]);
+    while let Some((digest, depth)) = queue.pop_front() {
+        if depth > max_depth {
+            continue;
+        }
+        for descriptor in fetch(&digest)?.manifests {
+            let child = match &descriptor.digest {
+                Some(child) => child.to_string(),
+                None => continue,
+            };
+            if !visited.insert(child.clone()) {
+                continue;
+            }
+            queue.push_back((child, depth + 1));
+            referrers.push(Referrer {
+                subject: digest.clone(),
+                descriptor,
+                depth,
+            });
+        }
+    }
+    Ok(referrers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn descriptor(digest: &str, media_type: &str) -> Descriptor {
+        Descriptor {
+            media_type: Some(media_type.to_string()),
+            digest: Some(digest.parse().unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_discover() {
+        let image = format!("sha256:{}", "a".repeat(64));
+        let sig = format!("sha256:{}", "b".repeat(64));
+        let attestation = format!("sha256:{}", "c".repeat(64));
+        let graph = HashMap::from([
+            (
+                image.clone(),
+                vec![descriptor(&sig, MEDIA_TYPE_IMAGE_INDEX)],
+            ),
+            (
+                sig.clone(),
+                vec![
+                    descriptor(&attestation, "application/json"),
+                    descriptor(&image, "application/json"),
+                ],
+            ),
+        ]);
+        let fetch = |digest: &str| {
+            Ok(Index {
+                manifests: graph.get(digest).cloned().unwrap_or_default(),
+                ..Default::default()
+            })
+        };
+
+        let referrers = discover(&image, 1, fetch).unwrap();
+        assert_eq!(referrers.len(), 1);
+        assert!(referrers[0].is_index());
+
+        let referrers = discover(&image, 5, fetch).unwrap();
+        assert_eq!(referrers.len(), 2);
+        assert_eq!(referrers[1].subject, sig);
+        assert_eq!(referrers[1].depth, 2);
+    }
+}
@@ -0,0 +1,167 @@
+use super::parse::{parse, Document};
+use super::validation::pointer;
+use crate::error::Error;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Divergence is a difference between a document and its re-serialized form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Pointer is the RFC 6901 JSON pointer of the divergent field, empty for the whole document.
+    pub pointer: String,
+
+    /// Original is the value in the document, None if the field was added.
+    pub original: Option<Value>,
+
+    /// Reserialized is the value written back, None if the field was dropped.
+    pub reserialized: Option<Value>,
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "<absent>".to_string(),
+        };
+        write!(
+            f,
+            "{}: {} became {}",
+            if self.pointer.is_empty() {
+                "/"
+            } else {
+                &self.pointer
+            },
+            show(&self.original),
+            show(&self.reserialized)
+        )
+    }
+}
+
+/// roundtrip parses bytes like parse, serializes the document back and returns
+/// where the result differs from the input. Fields which were dropped, added or
+/// changed are reported by pointer. When the values are equal but the bytes are
+/// not, e.g. because of whitespace or key order, a single divergence for the
+/// whole document is reported. An empty result means re-serializing the
+/// document keeps its digest.
+pub fn roundtrip(bytes: &[u8]) -> Result<Vec<Divergence>, Error> {
+    let reserialized = match parse(bytes)? {
+        Document::Descriptor(descriptor) => serde_json::to_vec(&descriptor)?,
+        Document::Layout(layout) => serde_json::to_vec(&layout)?,
+        Document::Manifest(manifest) => serde_json::to_vec(&manifest)?,
+        Document::Index(index) => serde_json::to_vec(&index)?,
+        Document::Config(config) => serde_json::to_vec(&config)?,
+    };
+    let original: Value = serde_json::from_slice(bytes)?;
+    let written: Value = serde_json::from_slice(&reserialized)?;
+
+    let mut divergences = Vec::new();
+    diff("", Some(&original), Some(&written), &mut divergences);
+    if divergences.is_empty() && reserialized != bytes {
+        divergences.push(Divergence {
+            pointer: String::new(),
+            original: Some(Value::String(String::from_utf8_lossy(bytes).into_owned())),
+            reserialized: Some(Value::String(
+                String::from_utf8_lossy(&reserialized).into_owned(),
+            )),
+        });
+    }
+    Ok(divergences)
+}
+
+/// assert_roundtrip_identical panics, listing the divergent fields, unless the
+/// document is re-serialized byte for byte. It is meant for test suites
+/// checking that this crate won't alter the digests of given documents.
+pub fn assert_roundtrip_identical(bytes: &[u8]) {
+    let divergences = match roundtrip(bytes) {
+        Ok(divergences) => divergences,
+        Err(err) => panic!("document can't be parsed: {}", err),
+    };
+    if !divergences.is_empty() {
+        let lines = divergences
+            .iter()
+            .map(|divergence| divergence.to_string())
+            .collect::<Vec<_>>();
+        panic!(
+            "document isn't re-serialized identically:\n{}",
+            lines.join("\n")
+        );
+    }
+}
+
+fn diff(at: &str, original: Option<&Value>, written: Option<&Value>, out: &mut Vec<Divergence>) {
+    match (original, written) {
+        (Some(Value::Object(original)), Some(Value::Object(written))) => {
+            let keys = original
+                .keys()
+                .chain(written.keys())
+                .collect::<BTreeSet<_>>();
+            for key in keys {
+                diff(&pointer(at, key), original.get(key), written.get(key), out);
+            }
+        }
+        (Some(Value::Array(original)), Some(Value::Array(written))) => {
+            for i in 0..original.len().max(written.len()) {
+                diff(
+                    &pointer(at, &i.to_string()),
+                    original.get(i),
+                    written.get(i),
+                    out,
+                );
+            }
+        }
+        (original, written) if original != written => out.push(Divergence {
+            pointer: at.to_string(),
+            original: original.cloned(),
+            reserialized: written.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::specs::v1::descriptor::Descriptor;
+    use crate::specs::v1::index::Index;
+
+    #[test]
+    fn test_roundtrip() {
+        let index = Index {
+            schema_version: 2,
+            manifests: vec![Descriptor {
+                media_type: Some("application/vnd.oci.image.manifest.v1+json".to_string()),
+                size: 7,
+                digest: Some(format!("sha256:{}", "a".repeat(64)).parse().unwrap()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let bytes = serde_json::to_vec(&index).unwrap();
+        assert!(roundtrip(&bytes).unwrap().is_empty());
+        assert_roundtrip_identical(&bytes);
+
+        let mut value: Value = serde_json::from_slice(&bytes).unwrap();
+        value["manifests"][0]["x-vendor"] = Value::from(true);
+        let divergences = roundtrip(&serde_json::to_vec(&value).unwrap()).unwrap();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].pointer, "/manifests/0/x-vendor");
+        assert_eq!(divergences[0].reserialized, None);
+
+        let pretty = serde_json::to_vec_pretty(&index).unwrap();
+        let divergences = roundtrip(&pretty).unwrap();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].pointer, "");
+    }
+
+    #[test]
+    #[should_panic(expected = "/manifests/0/x-vendor")]
+    fn test_assert_roundtrip_identical() {
+        assert_roundtrip_identical(
+            format!(
+                r#"{{"schemaVersion":2,"manifests":[{{"digest":"sha256:{}","size":1,"x-vendor":1}}]}}"#,
+                "a".repeat(64)
+            )
+            .as_bytes(),
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use super::annotations::ANNOTATION_CREATED;
+use super::descriptor::Descriptor;
+use super::index::Index;
+use super::manifest::Manifest;
+use super::mediatype::{MEDIA_TYPE_EMPTY_JSON, MEDIA_TYPE_IMAGE_MANIFEST};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// ARTIFACT_TYPE_VULNERABILITY_SCAN is the artifact type of a manifest
+/// attaching a vulnerability scan report to its subject.
+pub const ARTIFACT_TYPE_VULNERABILITY_SCAN: &str =
+    "application/vnd.io.github.tosone.vulnerability-scan.v1+json";
+
+/// MEDIA_TYPE_SARIF is the media type of a report in the SARIF format.
+pub const MEDIA_TYPE_SARIF: &str = "application/sarif+json";
+
+/// MEDIA_TYPE_TRIVY_REPORT is the media type of a report in the Trivy JSON format.
+pub const MEDIA_TYPE_TRIVY_REPORT: &str = "application/vnd.aquasec.trivy.report.v1+json";
+
+/// ANNOTATION_SCANNER is the annotation key on a scan manifest for the name
+/// of the scanner which produced the report, e.g. `trivy`.
+pub const ANNOTATION_SCANNER: &str = "io.github.tosone.scan.scanner";
+
+/// Scan is a vulnerability scan found among the referrers of a subject.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scan<'a> {
+    /// Scanner is the name of the scanner which produced the report.
+    pub scanner: &'a str,
+
+    /// Created is the time at which the scan was made.
+    pub created: DateTime<Utc>,
+
+    /// Descriptor describes the scan manifest.
+    pub descriptor: &'a Descriptor,
+}
+
+/// scan_manifest returns the manifest attaching a scan report to subject.
+/// report describes the report blob, e.g. a SARIF document, and becomes the
+/// only layer. The scanner and the creation time are annotations of the
+/// manifest, so that registries copy them into the referrers index.
+pub fn scan_manifest(
+    subject: &Descriptor,
+    report: Descriptor,
+    scanner: &str,
+    created: DateTime<Utc>,
+) -> Manifest {
+    Manifest {
+        schema_version: 2,
+        media_type: Some(MEDIA_TYPE_IMAGE_MANIFEST.to_string()),
+        artifact_type: Some(ARTIFACT_TYPE_VULNERABILITY_SCAN.to_string()),
+        config: Descriptor::from_bytes(MEDIA_TYPE_EMPTY_JSON, b"{}"),
+        layers: vec![report],
+        subject: Some(subject.clone()),
+        annotations: Some(HashMap::from([
+            (ANNOTATION_SCANNER.to_string(), scanner.to_string()),
+            (
+                ANNOTATION_CREATED.to_string(),
+                created.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
+        ])),
+    }
+}
+
+/// scan_report returns the report layer of a scan manifest, or None if the
+/// manifest isn't a scan.
+pub fn scan_report(manifest: &Manifest) -> Option<&Descriptor> {
+    match manifest.artifact_type.as_deref() {
+        Some(ARTIFACT_TYPE_VULNERABILITY_SCAN) => manifest.layers.first(),
+        _ => None,
+    }
+}
+
+/// scans returns the vulnerability scans among the referrers of a subject,
+/// recognized by their artifact type. Scans without a scanner or a valid
+/// RFC 3339 creation time are skipped.
+pub fn scans(referrers: &Index) -> Vec<Scan<'_>> {
+    referrers
+        .manifests
+        .iter()
+        .filter(|descriptor| {
+            descriptor.artifact_type.as_deref() == Some(ARTIFACT_TYPE_VULNERABILITY_SCAN)
+        })
+        .filter_map(|descriptor| {
+            let annotations = descriptor.annotations.as_ref()?;
+            let scanner = annotations.get(ANNOTATION_SCANNER)?;
+            let created =
+                DateTime::parse_from_rfc3339(annotations.get(ANNOTATION_CREATED)?).ok()?;
+            Some(Scan {
+                scanner,
+                created: created.with_timezone(&Utc),
+                descriptor,
+            })
+        })
+        .collect()
+}
+
+/// latest_scans returns the most recent scan of each scanner among the
+/// referrers of a subject, ordered by scanner. Of two scans created at the
+/// same time, the one listed last wins.
+pub fn latest_scans(referrers: &Index) -> Vec<Scan<'_>> {
+    let mut latest = BTreeMap::new();
+    for scan in scans(referrers) {
+        match latest.get(scan.scanner) {
+            Some(Scan { created, .. }) if *created > scan.created => {}
+            _ => {
+                latest.insert(scan.scanner, scan);
+            }
+        }
+    }
+    latest.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::specs::v1::annotations::lint_keys;
+    use crate::specs::v1::validation::Validate;
+    use chrono::TimeZone;
+
+    fn referrer(scanner: &str, created: &str) -> Descriptor {
+        Descriptor {
+            media_type: Some(MEDIA_TYPE_IMAGE_MANIFEST.to_string()),
+            artifact_type: Some(ARTIFACT_TYPE_VULNERABILITY_SCAN.to_string()),
+            annotations: Some(HashMap::from([
+                (ANNOTATION_SCANNER.to_string(), scanner.to_string()),
+                (ANNOTATION_CREATED.to_string(), created.to_string()),
+            ])),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_scan_manifest() {
+        let subject = Descriptor::from_bytes(MEDIA_TYPE_IMAGE_MANIFEST, b"{}");
+        let report = Descriptor::from_bytes(MEDIA_TYPE_SARIF, br#"{"version":"2.1.0"}"#);
+        let created = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let manifest = scan_manifest(&subject, report.clone(), "trivy", created);
+
+        assert!(manifest.validate().errors.is_empty());
+        assert_eq!(manifest.subject, Some(subject));
+        assert_eq!(scan_report(&manifest), Some(&report));
+        assert!(scan_report(&Manifest::default()).is_none());
+        let annotations = manifest.annotations.unwrap();
+        assert_eq!(annotations[ANNOTATION_CREATED], "2024-05-01T12:00:00Z");
+        assert!(lint_keys(&annotations).is_empty());
+    }
+
+    #[test]
+    fn test_latest_scans() {
+        let referrers = Index {
+            schema_version: 2,
+            manifests: vec![
+                referrer("trivy", "2024-05-01T12:00:00Z"),
+                referrer("grype", "2024-05-02T00:00:00Z"),
+                referrer("trivy", "2024-05-03T08:00:00+02:00"),
+                referrer("trivy", "2024-05-02T00:00:00Z"),
+                referrer("grype", "yesterday"),
+                Descriptor {
+                    artifact_type: Some("application/vnd.example.sbom.v1+json".to_string()),
+                    ..referrer("trivy", "2024-06-01T00:00:00Z")
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(scans(&referrers).len(), 4);
+
+        let latest = latest_scans(&referrers);
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].scanner, "grype");
+        assert_eq!(latest[0].descriptor, &referrers.manifests[1]);
+        assert_eq!(latest[1].scanner, "trivy");
+        assert_eq!(latest[1].descriptor, &referrers.manifests[2]);
+        assert_eq!(
+            latest[1].created,
+            Utc.with_ymd_and_hms(2024, 5, 3, 6, 0, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,88 @@
+const DECIMAL_UNITS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+
+/// human_size returns a human-readable approximation of a size in bytes
+/// using decimal (SI) units, for example `1.5kB` or `2.75MB`.
+pub fn human_size(size: u64) -> String {
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < DECIMAL_UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        return format!("{}{}", size, DECIMAL_UNITS[0]);
+    }
+    let value = format!("{:.2}", value);
+    let value = value.trim_end_matches('0').trim_end_matches('.');
+    format!("{}{}", value, DECIMAL_UNITS[unit])
+}
+
+/// human_duration returns a human-readable approximation of a duration,
+/// for example `About a minute` or `3 days`.
+pub fn human_duration(duration: chrono::Duration) -> String {
+    let seconds = duration.num_seconds();
+    if seconds < 1 {
+        return "Less than a second".to_string();
+    } else if seconds == 1 {
+        return "1 second".to_string();
+    } else if seconds < 60 {
+        return format!("{} seconds", seconds);
+    }
+    let minutes = duration.num_minutes();
+    if minutes == 1 {
+        return "About a minute".to_string();
+    } else if minutes < 60 {
+        return format!("{} minutes", minutes);
+    }
+    let hours = (duration.num_seconds() as f64 / 3600.0).round() as i64;
+    if hours == 1 {
+        "About an hour".to_string()
+    } else if hours < 48 {
+        format!("{} hours", hours)
+    } else if hours < 24 * 7 * 2 {
+        format!("{} days", hours / 24)
+    } else if hours < 24 * 30 * 2 {
+        format!("{} weeks", hours / 24 / 7)
+    } else if hours < 24 * 365 * 2 {
+        format!("{} months", hours / 24 / 30)
+    } else {
+        format!("{} years", hours / 24 / 365)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0B");
+        assert_eq!(human_size(999), "999B");
+        assert_eq!(human_size(1500), "1.5kB");
+        assert_eq!(human_size(2_750_000), "2.75MB");
+        assert_eq!(human_size(1_000_000_000), "1GB");
+    }
+
+    #[test]
+    fn test_human_duration() {
+        assert_eq!(
+            human_duration(chrono::Duration::milliseconds(10)),
+            "Less than a second"
+        );
+        assert_eq!(human_duration(chrono::Duration::seconds(30)), "30 seconds");
+        assert_eq!(
+            human_duration(chrono::Duration::seconds(90)),
+            "About a minute"
+        );
+        assert_eq!(human_duration(chrono::Duration::minutes(59)), "59 minutes");
+        assert_eq!(
+            human_duration(chrono::Duration::minutes(70)),
+            "About an hour"
+        );
+        assert_eq!(human_duration(chrono::Duration::hours(30)), "30 hours");
+        assert_eq!(human_duration(chrono::Duration::days(3)), "3 days");
+        assert_eq!(human_duration(chrono::Duration::days(21)), "3 weeks");
+        assert_eq!(human_duration(chrono::Duration::days(90)), "3 months");
+        assert_eq!(human_duration(chrono::Duration::days(1000)), "2 years");
+    }
+}
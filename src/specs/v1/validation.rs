@@ -0,0 +1,690 @@
+use super::annotations::{ANNOTATION_CREATED, ANNOTATION_SOURCE};
+use super::config::Image;
+use super::descriptor::{Descriptor, Platform, MAX_SIZE};
+use super::env::is_valid as is_valid_env;
+use super::index::Index;
+use super::manifest::Manifest;
+use super::mediatype::*;
+use super::parse::{parse, Document};
+use super::ports::PortProtocol;
+use super::volumes::is_absolute;
+use crate::error::Error;
+use std::collections::HashMap;
+
+/// Code is the stable machine-readable identifier of a validation failure.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Code {
+    /// The schemaVersion is not the one defined by the specification.
+    #[serde(rename = "E_SCHEMA_VERSION")]
+    SchemaVersion,
+    /// A media type is missing, malformed or not the expected one.
+    #[serde(rename = "E_MEDIA_TYPE")]
+    MediaType,
+    /// A digest is missing or doesn't follow the digest grammar.
+    #[serde(rename = "E_DIGEST_FORMAT")]
+    DigestFormat,
+    /// A size is out of range.
+    #[serde(rename = "E_SIZE")]
+    Size,
+    /// A platform is missing required fields.
+    #[serde(rename = "E_PLATFORM")]
+    Platform,
+    /// A required field of the image config is missing or malformed.
+    #[serde(rename = "E_CONFIG")]
+    Config,
+    /// An annotation key doesn't follow the annotation rules.
+    #[serde(rename = "E_ANNOTATION_KEY")]
+    AnnotationKey,
+    /// An annotation required by the validation profile is missing.
+    #[serde(rename = "E_ANNOTATION_MISSING")]
+    AnnotationMissing,
+}
+
+impl Code {
+    /// as_str returns the string form of the code, e.g. `E_DIGEST_FORMAT`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Code::SchemaVersion => "E_SCHEMA_VERSION",
+            Code::MediaType => "E_MEDIA_TYPE",
+            Code::DigestFormat => "E_DIGEST_FORMAT",
+            Code::Size => "E_SIZE",
+            Code::Platform => "E_PLATFORM",
+            Code::Config => "E_CONFIG",
+            Code::AnnotationKey => "E_ANNOTATION_KEY",
+            Code::AnnotationMissing => "E_ANNOTATION_MISSING",
+        }
+    }
+}
+
+impl std::fmt::Display for Code {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Severity tells whether a finding violates a MUST (error) or a SHOULD (warning) of the specification.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    #[serde(rename = "error")]
+    Error,
+    #[serde(rename = "warning")]
+    Warning,
+}
+
+/// ValidationError describes a single validation failure.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// Code is the stable identifier of the failure.
+    #[serde(rename = "code")]
+    pub code: Code,
+
+    /// Severity is the severity of the failure.
+    #[serde(rename = "severity")]
+    pub severity: Severity,
+
+    /// Pointer is the RFC 6901 JSON pointer of the offending field, e.g. `/layers/0/digest`.
+    #[serde(rename = "pointer")]
+    pub pointer: String,
+
+    /// Message is a human-readable description of the failure.
+    #[serde(rename = "message")]
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}: {}", self.code, self.pointer, self.message)
+    }
+}
+
+/// ValidationReport collects the validation failures of a document.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ValidationReport {
+    #[serde(rename = "errors")]
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    /// is_valid reports whether the report contains no error severity failures.
+    pub fn is_valid(&self) -> bool {
+        !self
+            .errors
+            .iter()
+            .any(|error| error.severity == Severity::Error)
+    }
+
+    /// error records an error severity failure at `pointer`.
+    pub fn error(&mut self, code: Code, pointer: &str, message: String) {
+        self.push(code, Severity::Error, pointer, message);
+    }
+
+    /// warning records a warning severity failure at `pointer`.
+    pub fn warning(&mut self, code: Code, pointer: &str, message: String) {
+        self.push(code, Severity::Warning, pointer, message);
+    }
+
+    fn push(&mut self, code: Code, severity: Severity, pointer: &str, message: String) {
+        self.errors.push(ValidationError {
+            code,
+            severity,
+            pointer: pointer.to_string(),
+            message,
+        });
+    }
+
+    /// into_result returns the report as `Error::Validation` unless it is valid.
+    pub fn into_result(self) -> Result<(), Error> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(Error::Validation(self))
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors = self
+            .errors
+            .iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>();
+        f.write_str(&errors.join("; "))
+    }
+}
+
+/// pointer appends an RFC 6901 escaped reference token to a JSON pointer.
+pub fn pointer(base: &str, token: &str) -> String {
+    format!("{}/{}", base, token.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Validate is implemented by documents which can be checked against the specification.
+pub trait Validate {
+    /// validate_at validates the document located at the JSON pointer `at`
+    /// and records failures into `report`.
+    fn validate_at(&self, at: &str, report: &mut ValidationReport);
+
+    /// validate validates the document and returns the report.
+    fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        self.validate_at("", &mut report);
+        report
+    }
+}
+
+fn validate_digest(digest: &str, at: &str, report: &mut ValidationReport) {
+    let valid = match digest.split_once(':') {
+        Some((name, _)) => crate::image_digest::digest::Digest {
+            name: name.to_string(),
+            digest: digest.to_string(),
+        }
+        .validate()
+        .is_ok(),
+        None => false,
+    };
+    if !valid {
+        report.error(
+            Code::DigestFormat,
+            at,
+            format!("invalid digest {:?}", digest),
+        );
+    }
+}
+
+fn validate_annotations(
+    annotations: &Option<HashMap<String, String>>,
+    at: &str,
+    report: &mut ValidationReport,
+) {
+    if let Some(annotations) = annotations {
+        let at = pointer(at, "annotations");
+        for (key, warning) in super::annotations::lint_keys(annotations) {
+            report.warning(Code::AnnotationKey, &pointer(&at, &key), warning);
+        }
+    }
+}
+
+fn validate_schema_version(schema_version: isize, at: &str, report: &mut ValidationReport) {
+    if schema_version != 2 {
+        report.error(
+            Code::SchemaVersion,
+            &pointer(at, "schemaVersion"),
+            format!("schemaVersion must be 2, got {}", schema_version),
+        );
+    }
+}
+
+fn validate_document_media_type(
+    media_type: &Option<String>,
+    expected: &str,
+    at: &str,
+    report: &mut ValidationReport,
+) {
+    if let Some(media_type) = media_type {
+        if media_type != expected {
+            report.error(
+                Code::MediaType,
+                &pointer(at, "mediaType"),
+                format!("mediaType must be {}, got {}", expected, media_type),
+            );
+        }
+    }
+}
+
+impl Validate for Descriptor {
+    fn validate_at(&self, at: &str, report: &mut ValidationReport) {
+        match &self.media_type {
+            Some(media_type) if is_valid_media_type(media_type) => {}
+            Some(media_type) => report.error(
+                Code::MediaType,
+                &pointer(at, "mediaType"),
+                format!("invalid media type {:?}", media_type),
+            ),
+            None => report.error(
+                Code::MediaType,
+                &pointer(at, "mediaType"),
+                "mediaType is required".to_string(),
+            ),
+        }
+        if let Some(artifact_type) = &self.artifact_type {
+            if !is_valid_media_type(artifact_type) {
+                report.error(
+                    Code::MediaType,
+                    &pointer(at, "artifactType"),
+                    format!("invalid artifact type {:?}", artifact_type),
+                );
+            }
+        }
+        match &self.digest {
+            Some(digest) => validate_digest(&digest.digest, &pointer(at, "digest"), report),
+            None => report.error(
+                Code::DigestFormat,
+                &pointer(at, "digest"),
+                "digest is required".to_string(),
+            ),
+        }
+        if self.size > MAX_SIZE {
+            report.error(
+                Code::Size,
+                &pointer(at, "size"),
+                format!("size must not exceed {}, got {}", MAX_SIZE, self.size),
+            );
+        }
+        if let Some(platform) = &self.platform {
+            platform.validate_at(&pointer(at, "platform"), report);
+        }
+        validate_annotations(&self.annotations, at, report);
+    }
+}
+
+impl Validate for Platform {
+    fn validate_at(&self, at: &str, report: &mut ValidationReport) {
+        if self.architecture.is_empty() {
+            report.error(
+                Code::Platform,
+                &pointer(at, "architecture"),
+                "architecture is required".to_string(),
+            );
+        }
+        if self.os.is_empty() {
+            report.error(
+                Code::Platform,
+                &pointer(at, "os"),
+                "os is required".to_string(),
+            );
+        }
+    }
+}
+
+impl Validate for Manifest {
+    fn validate_at(&self, at: &str, report: &mut ValidationReport) {
+        validate_schema_version(self.schema_version, at, report);
+        validate_document_media_type(&self.media_type, MEDIA_TYPE_IMAGE_MANIFEST, at, report);
+        match &self.artifact_type {
+            Some(artifact_type) if !is_valid_media_type(artifact_type) => report.error(
+                Code::MediaType,
+                &pointer(at, "artifactType"),
+                format!("invalid artifact type {:?}", artifact_type),
+            ),
+            None if self.config.media_type.as_deref() == Some(MEDIA_TYPE_EMPTY_JSON) => report
+                .error(
+                    Code::MediaType,
+                    &pointer(at, "artifactType"),
+                    "artifactType is required when the config is empty".to_string(),
+                ),
+            _ => {}
+        }
+        self.config.validate_at(&pointer(at, "config"), report);
+        let layers = pointer(at, "layers");
+        for (i, layer) in self.layers.iter().enumerate() {
+            layer.validate_at(&pointer(&layers, &i.to_string()), report);
+        }
+        if let Some(subject) = &self.subject {
+            subject.validate_at(&pointer(at, "subject"), report);
+        }
+        validate_annotations(&self.annotations, at, report);
+    }
+}
+
+impl Validate for Index {
+    fn validate_at(&self, at: &str, report: &mut ValidationReport) {
+        validate_schema_version(self.schema_version, at, report);
+        validate_document_media_type(&self.media_type, MEDIA_TYPE_IMAGE_INDEX, at, report);
+        if let Some(artifact_type) = &self.artifact_type {
+            if !is_valid_media_type(artifact_type) {
+                report.error(
+                    Code::MediaType,
+                    &pointer(at, "artifactType"),
+                    format!("invalid artifact type {:?}", artifact_type),
+                );
+            }
+        }
+        let manifests = pointer(at, "manifests");
+        for (i, manifest) in self.manifests.iter().enumerate() {
+            manifest.validate_at(&pointer(&manifests, &i.to_string()), report);
+        }
+        if let Some(subject) = &self.subject {
+            subject.validate_at(&pointer(at, "subject"), report);
+        }
+        validate_annotations(&self.annotations, at, report);
+    }
+}
+
+impl Validate for Image {
+    fn validate_at(&self, at: &str, report: &mut ValidationReport) {
+        if self.architecture.is_empty() {
+            report.error(
+                Code::Config,
+                &pointer(at, "architecture"),
+                "architecture is required".to_string(),
+            );
+        }
+        if self.os.is_empty() {
+            report.error(
+                Code::Config,
+                &pointer(at, "os"),
+                "os is required".to_string(),
+            );
+        }
+        let rootfs = pointer(at, "rootfs");
+        if self.rootfs.type_ != "layers" {
+            report.error(
+                Code::Config,
+                &pointer(&rootfs, "type"),
+                format!("rootfs type must be layers, got {:?}", self.rootfs.type_),
+            );
+        }
+        let diff_ids = pointer(&rootfs, "diff_ids");
+        for (i, diff_id) in self.rootfs.diff_ids.iter().enumerate() {
+            validate_digest(diff_id, &pointer(&diff_ids, &i.to_string()), report);
+        }
+        if let Some(ports) = self
+            .config
+            .as_ref()
+            .and_then(|config| config.exposed_ports.as_ref())
+        {
+            let exposed_ports = pointer(&pointer(at, "config"), "ExposedPorts");
+            let mut keys = ports.keys().collect::<Vec<_>>();
+            keys.sort();
+            for key in keys {
+                if let Err(err) = key.parse::<PortProtocol>() {
+                    report.error(Code::Config, &pointer(&exposed_ports, key), err.to_string());
+                }
+            }
+        }
+        if let Some(env) = self.config.as_ref().and_then(|config| config.env.as_ref()) {
+            let at = pointer(&pointer(at, "config"), "Env");
+            for (i, entry) in env.iter().enumerate() {
+                if !is_valid_env(entry) {
+                    report.error(
+                        Code::Config,
+                        &pointer(&at, &i.to_string()),
+                        format!("environment variable {:?} is not KEY=value", entry),
+                    );
+                }
+            }
+        }
+        if let Some(volumes) = self
+            .config
+            .as_ref()
+            .and_then(|config| config.volumes.as_ref())
+        {
+            let at = pointer(&pointer(at, "config"), "Volumes");
+            for path in volumes.iter().filter(|path| !is_absolute(path)) {
+                report.error(
+                    Code::Config,
+                    &pointer(&at, path),
+                    format!("volume {:?} is not an absolute path", path),
+                );
+            }
+        }
+    }
+}
+
+/// Profile selects how strictly a document is validated, depending on what it is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// RegistryPush reports only the errors a registry must reject a push for:
+    /// malformed documents and descriptors. Config contents and annotation
+    /// lints are ignored.
+    RegistryPush,
+    /// RuntimeRun reports the errors which prevent running the image: on top
+    /// of the standard checks, every manifest of an index must declare its
+    /// platform. Annotation lints are ignored.
+    RuntimeRun,
+    /// Archival reports everything, treats warnings as errors and requires
+    /// the created and source annotations on manifests and indexes, so the
+    /// image stays traceable in the long term.
+    Archival,
+}
+
+impl Profile {
+    fn apply(&self, document: &Document, report: &mut ValidationReport) {
+        match self {
+            Profile::RegistryPush => report.errors.retain(|error| {
+                error.severity == Severity::Error
+                    && error.code != Code::Config
+                    && error.code != Code::Platform
+            }),
+            Profile::RuntimeRun => {
+                report
+                    .errors
+                    .retain(|error| error.code != Code::AnnotationKey);
+                if let Document::Index(index) = document {
+                    for (i, manifest) in index.manifests.iter().enumerate() {
+                        if manifest.platform.is_none() {
+                            report.error(
+                                Code::Platform,
+                                &pointer(&pointer("/manifests", &i.to_string()), "platform"),
+                                "platform is required to select a manifest".to_string(),
+                            );
+                        }
+                    }
+                }
+            }
+            Profile::Archival => {
+                for error in &mut report.errors {
+                    error.severity = Severity::Error;
+                }
+                let annotations = match document {
+                    Document::Manifest(manifest) => Some(&manifest.annotations),
+                    Document::Index(index) => Some(&index.annotations),
+                    _ => None,
+                };
+                if let Some(annotations) = annotations {
+                    for key in [ANNOTATION_CREATED, ANNOTATION_SOURCE] {
+                        if !annotations.as_ref().is_some_and(|a| a.contains_key(key)) {
+                            report.error(
+                                Code::AnnotationMissing,
+                                &pointer("/annotations", key),
+                                format!("annotation {} is required", key),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// validate_with_profile parses a manifest, index, config or descriptor and
+/// validates it according to profile. It fails only if the document can't be parsed.
+pub fn validate_with_profile(bytes: &[u8], profile: Profile) -> Result<ValidationReport, Error> {
+    let document = parse(bytes)?;
+    let mut report = match &document {
+        Document::Descriptor(descriptor) => descriptor.validate(),
+        Document::Manifest(manifest) => manifest.validate(),
+        Document::Index(index) => index.validate(),
+        Document::Config(config) => config.validate(),
+        Document::Layout(_) => ValidationReport::default(),
+    };
+    profile.apply(&document, &mut report);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_manifest() {
+        let manifest = Manifest {
+            schema_version: 1,
+            config: Descriptor {
+                media_type: Some(MEDIA_TYPE_IMAGE_CONFIG.to_string()),
+                digest: Some(
+                    "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+                        .parse()
+                        .unwrap(),
+                ),
+                size: 5,
+                ..Default::default()
+            },
+            layers: vec![Descriptor {
+                media_type: Some("not a media type".to_string()),
+                // Digest fields are public, so an invalid digest can still be built by hand.
+                digest: Some(crate::image_digest::digest::Digest {
+                    name: "sha256".to_string(),
+                    digest: "2cf24dba".to_string(),
+                }),
+                size: u64::MAX,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let report = manifest.validate();
+        assert!(!report.is_valid());
+        let found = report
+            .errors
+            .iter()
+            .map(|error| (error.code.as_str(), error.pointer.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("E_SCHEMA_VERSION", "/schemaVersion"),
+                ("E_MEDIA_TYPE", "/layers/0/mediaType"),
+                ("E_DIGEST_FORMAT", "/layers/0/digest"),
+                ("E_SIZE", "/layers/0/size"),
+            ]
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["errors"][0]["code"], "E_SCHEMA_VERSION");
+    }
+
+    #[test]
+    fn test_validate_subject() {
+        let config = Descriptor::from_bytes(MEDIA_TYPE_IMAGE_CONFIG, b"{}");
+        let manifest = Manifest {
+            schema_version: 2,
+            config: config.clone(),
+            subject: Some(Descriptor {
+                digest: None,
+                ..config
+            }),
+            ..Default::default()
+        };
+        let report = manifest.validate();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].pointer, "/subject/digest");
+    }
+
+    #[test]
+    fn test_validate_artifact_type() {
+        let mut manifest = Manifest {
+            schema_version: 2,
+            config: Descriptor::from_bytes(MEDIA_TYPE_EMPTY_JSON, b"{}"),
+            ..Default::default()
+        };
+        let pointers = |manifest: &Manifest| {
+            manifest
+                .validate()
+                .errors
+                .into_iter()
+                .map(|error| error.pointer)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pointers(&manifest), vec!["/artifactType"]);
+        assert_eq!(
+            manifest.effective_artifact_type(),
+            Some(MEDIA_TYPE_EMPTY_JSON)
+        );
+
+        manifest.artifact_type = Some("application/vnd.example.sbom.v1+json".to_string());
+        assert!(pointers(&manifest).is_empty());
+        assert_eq!(
+            manifest.effective_artifact_type(),
+            Some("application/vnd.example.sbom.v1+json")
+        );
+
+        manifest.artifact_type = Some("sbom".to_string());
+        assert_eq!(pointers(&manifest), vec!["/artifactType"]);
+    }
+
+    #[test]
+    fn test_validate_image_config() {
+        let mut config = crate::specs::v1::config::ImageConfig::default();
+        config.expose("8080/tcp".parse().unwrap());
+        let ports = config.exposed_ports.as_mut().unwrap();
+        ports.insert("80/http".to_string(), Default::default());
+        ports.insert("70000".to_string(), Default::default());
+        config.volumes = Some(serde_json::from_str(r#"{"/data":{},"cache":{}}"#).unwrap());
+        config.env = Some(vec!["A=1".to_string(), "B".to_string()]);
+        let image = Image {
+            config: Some(config),
+            ..Image::scratch(&"linux/amd64".parse().unwrap())
+        };
+        let pointers = image
+            .validate()
+            .errors
+            .into_iter()
+            .map(|error| error.pointer)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pointers,
+            vec![
+                "/config/ExposedPorts/70000",
+                "/config/ExposedPorts/80~1http",
+                "/config/Env/1",
+                "/config/Volumes/cache"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_annotation_keys() {
+        let mut annotations = HashMap::new();
+        annotations.insert(
+            "org.opencontainers.image.custom".to_string(),
+            "x".to_string(),
+        );
+        annotations.insert("com.example.build".to_string(), "x".to_string());
+        let manifest = Manifest {
+            schema_version: 2,
+            config: Descriptor::from_bytes(MEDIA_TYPE_IMAGE_CONFIG, b"{}"),
+            annotations: Some(annotations),
+            ..Default::default()
+        };
+        let report = manifest.validate();
+        assert!(report.is_valid());
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].code, Code::AnnotationKey);
+        assert_eq!(
+            report.errors[0].pointer,
+            "/annotations/org.opencontainers.image.custom"
+        );
+    }
+
+    #[test]
+    fn test_pointer_escaping() {
+        assert_eq!(pointer("/annotations", "a/b~c"), "/annotations/a~1b~0c");
+    }
+
+    #[test]
+    fn test_validate_with_profile() {
+        let index = br#"{"schemaVersion":2,"manifests":[{"mediaType":"application/vnd.oci.image.manifest.v1+json","digest":"sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824","size":5}],"annotations":{"unprefixed":"x"}}"#;
+
+        let report = validate_with_profile(index, Profile::RegistryPush).unwrap();
+        assert!(report.errors.is_empty());
+
+        let report = validate_with_profile(index, Profile::RuntimeRun).unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.errors[0].pointer, "/manifests/0/platform");
+
+        let report = validate_with_profile(index, Profile::Archival).unwrap();
+        let codes = report
+            .errors
+            .iter()
+            .map(|error| error.code)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            codes,
+            vec![
+                Code::AnnotationKey,
+                Code::AnnotationMissing,
+                Code::AnnotationMissing
+            ]
+        );
+        assert!(!report.is_valid());
+    }
+}
@@ -0,0 +1,132 @@
+use super::config::Nothing;
+use crate::error::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// VolumeSet is the set of directories of the Volumes field of an image
+/// config. It serializes as the map of empty objects defined by the
+/// specification, e.g. `{"/data":{}}`, with the paths in ascending order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VolumeSet(BTreeSet<String>);
+
+impl VolumeSet {
+    /// new returns an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// insert adds path to the set, without its trailing `/`, and reports
+    /// whether it wasn't already present. It fails if path isn't absolute.
+    pub fn insert(&mut self, path: &str) -> Result<bool, Error> {
+        if !is_absolute(path) {
+            return Err(Error::InvalidInput(format!(
+                "volume {:?} is not an absolute path",
+                path
+            )));
+        }
+        Ok(self.0.insert(trim(path).to_string()))
+    }
+
+    /// remove removes path from the set and reports whether it was present.
+    pub fn remove(&mut self, path: &str) -> bool {
+        self.0.remove(trim(path))
+    }
+
+    /// contains reports whether path is in the set, ignoring a trailing `/`.
+    pub fn contains(&self, path: &str) -> bool {
+        self.0.contains(trim(path))
+    }
+
+    /// iter returns the paths in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// is_absolute reports whether path is an absolute Unix path, e.g. `/data`, or
+/// an absolute Windows path, e.g. `C:\data`.
+pub fn is_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && (bytes[2] == b'\\' || bytes[2] == b'/'))
+}
+
+// trim removes the trailing separators of a path, keeping a root such as `/`
+// or `C:\` intact.
+fn trim(path: &str) -> &str {
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    if trimmed.len() < path.len() && (trimmed.is_empty() || trimmed.ends_with(':')) {
+        &path[..trimmed.len() + 1]
+    } else {
+        trimmed
+    }
+}
+
+impl Serialize for VolumeSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|path| (path, Nothing {})))
+    }
+}
+
+impl<'de> Deserialize<'de> for VolumeSet {
+    /// deserialize keeps the paths as written, see validation for the paths
+    /// which aren't absolute.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let volumes = BTreeMap::<String, Nothing>::deserialize(deserializer)?;
+        Ok(VolumeSet(volumes.into_keys().collect()))
+    }
+}
+
+impl<'a> IntoIterator for &'a VolumeSet {
+    type Item = &'a String;
+    type IntoIter = std::collections::btree_set::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert() {
+        let mut volumes = VolumeSet::new();
+        assert!(volumes.insert("/var/lib/data/").unwrap());
+        assert!(!volumes.insert("/var/lib/data").unwrap());
+        assert!(volumes.insert("C:\\data").unwrap());
+        assert!(volumes.insert("/").unwrap());
+        assert!(volumes.insert("data").is_err());
+        assert!(volumes.insert("").is_err());
+        assert!(volumes.contains("/var/lib/data/"));
+        assert_eq!(
+            volumes.iter().collect::<Vec<_>>(),
+            vec!["/", "/var/lib/data", "C:\\data"]
+        );
+        assert!(volumes.remove("/var/lib/data"));
+        assert_eq!(volumes.len(), 2);
+    }
+
+    #[test]
+    fn test_serde() {
+        let json = r#"{"/b":{},"/a":{}}"#;
+        let volumes: VolumeSet = serde_json::from_str(json).unwrap();
+        assert!(volumes.contains("/a") && volumes.contains("/b"));
+        assert_eq!(
+            serde_json::to_string(&volumes).unwrap(),
+            r#"{"/a":{},"/b":{}}"#
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use super::Lossy;
+use crate::error::Error;
+use crate::image_digest::digest::Digest;
+use crate::specs::v1;
+use crate::specs::v1::validation::pointer;
+use std::collections::HashMap;
+
+pub use crate::specs::v1::descriptor::Platform;
+
+/// Descriptor is the image-spec v1.1 descriptor. Unlike the v1 descriptor,
+/// the media type and digest, which v1.1 requires, aren't optional, so
+/// converting from v1 fails when they are missing.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Descriptor {
+    /// MediaType is the media type of the object this schema refers to.
+    #[serde(rename = "mediaType")]
+    pub media_type: String,
+
+    /// Digest is the digest of the targeted content.
+    #[serde(rename = "digest")]
+    pub digest: Digest,
+
+    /// Size specifies the size in bytes of the blob.
+    #[serde(rename = "size", deserialize_with = "v1::descriptor::deserialize_size")]
+    pub size: u64,
+
+    /// URLs specifies a list of URLs from which this object MAY be downloaded
+    #[serde(rename = "urls", skip_serializing_if = "Option::is_none")]
+    pub urls: Option<Vec<String>>,
+
+    /// Annotations contains arbitrary metadata relating to the targeted content.
+    #[serde(rename = "annotations", skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<HashMap<String, String>>,
+
+    /// Data is the base64 encoded content of the targeted blob.
+    #[serde(rename = "data", skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+
+    /// Platform describes the platform which the image in the manifest runs on.
+    /// This should only be used when referring to a manifest.
+    #[serde(rename = "platform", skip_serializing_if = "Option::is_none")]
+    pub platform: Option<Platform>,
+
+    /// ArtifactType is the IANA media type of the artifact, when the
+    /// descriptor refers to an artifact manifest.
+    #[serde(rename = "artifactType", skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+}
+
+impl Descriptor {
+    /// to_v1_0 returns the descriptor without the fields image-spec v1.0
+    /// doesn't define, `data` and `artifactType`, reporting those it drops.
+    pub fn to_v1_0(&self) -> Lossy<v1::descriptor::Descriptor> {
+        let mut dropped = Vec::new();
+        let value = self.clone().strip_v1_1("", &mut dropped);
+        Lossy { value, dropped }
+    }
+
+    // strip_v1_1 converts the descriptor located at `at` to v1 without the
+    // v1.1 fields, recording the pointers of those which were set.
+    pub(super) fn strip_v1_1(
+        self,
+        at: &str,
+        dropped: &mut Vec<String>,
+    ) -> v1::descriptor::Descriptor {
+        let mut descriptor = v1::descriptor::Descriptor::from(self);
+        if descriptor.data.take().is_some() {
+            dropped.push(pointer(at, "data"));
+        }
+        if descriptor.artifact_type.take().is_some() {
+            dropped.push(pointer(at, "artifactType"));
+        }
+        descriptor
+    }
+}
+
+impl TryFrom<v1::descriptor::Descriptor> for Descriptor {
+    type Error = Error;
+
+    /// try_from fails if the v1 descriptor has no media type or digest.
+    fn try_from(descriptor: v1::descriptor::Descriptor) -> Result<Self, Self::Error> {
+        Ok(Descriptor {
+            media_type: descriptor
+                .media_type
+                .ok_or_else(|| Error::InvalidData("descriptor has no media type".to_string()))?,
+            digest: descriptor
+                .digest
+                .ok_or_else(|| Error::InvalidData("descriptor has no digest".to_string()))?,
+            size: descriptor.size,
+            urls: descriptor.urls,
+            annotations: descriptor.annotations,
+            data: descriptor.data,
+            platform: descriptor.platform,
+            artifact_type: descriptor.artifact_type,
+        })
+    }
+}
+
+impl From<Descriptor> for v1::descriptor::Descriptor {
+    fn from(descriptor: Descriptor) -> Self {
+        v1::descriptor::Descriptor {
+            media_type: Some(descriptor.media_type),
+            digest: Some(descriptor.digest),
+            size: descriptor.size,
+            urls: descriptor.urls,
+            annotations: descriptor.annotations,
+            data: descriptor.data,
+            platform: descriptor.platform,
+            artifact_type: descriptor.artifact_type,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptor_conversion() {
+        let v1 = v1::descriptor::Descriptor {
+            media_type: Some("application/vnd.oci.empty.v1+json".to_string()),
+            digest: Some(format!("sha256:{}", "a".repeat(64)).parse().unwrap()),
+            size: 2,
+            data: Some("e30=".to_string()),
+            artifact_type: Some("application/vnd.example+type".to_string()),
+            ..Default::default()
+        };
+        let descriptor = Descriptor::try_from(v1.clone()).unwrap();
+        assert_eq!(v1::descriptor::Descriptor::from(descriptor.clone()), v1);
+
+        let v1_0 = descriptor.to_v1_0();
+        assert!(!v1_0.is_lossless());
+        assert_eq!(v1_0.dropped, vec!["/data", "/artifactType"]);
+        assert_eq!(v1_0.value.data, None);
+        assert_eq!(v1_0.value.digest, v1.digest);
+
+        assert!(Descriptor::try_from(v1::descriptor::Descriptor {
+            media_type: None,
+            ..v1.clone()
+        })
+        .is_err());
+        assert!(Descriptor::try_from(v1::descriptor::Descriptor { digest: None, ..v1 }).is_err());
+        assert!(serde_json::from_str::<Descriptor>(r#"{"size": 2}"#).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use super::descriptor::Descriptor;
+use super::Lossy;
+use crate::error::Error;
+use crate::specs::v1;
+use crate::specs::v1::validation::pointer;
+
+/// Index is the image-spec v1.1 image index, whose descriptors are v1.1
+/// descriptors.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Index {
+    /// SchemaVersion is the image manifest schema that this image follows
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: isize,
+
+    /// MediaType specificies the type of this document data structure e.g. `application/vnd.oci.image.index.v1+json`
+    #[serde(rename = "mediaType", skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+
+    /// ArtifactType is the IANA media type of the artifact this index describes.
+    #[serde(rename = "artifactType", skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+
+    /// Manifests references platform specific manifests.
+    #[serde(rename = "manifests")]
+    pub manifests: Vec<Descriptor>,
+
+    /// Subject is an optional link from the image index to another manifest,
+    /// forming an association between the index and the other manifest.
+    #[serde(rename = "subject", skip_serializing_if = "Option::is_none")]
+    pub subject: Option<Descriptor>,
+
+    /// Annotations contains arbitrary metadata for the image index.
+    #[serde(rename = "annotations", skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<std::collections::HashMap<String, String>>,
+}
+
+impl Index {
+    /// to_v1_0 returns the index without the fields image-spec v1.0 doesn't
+    /// define, `artifactType`, `subject` and the v1.1 fields of its
+    /// descriptors, reporting those it drops.
+    pub fn to_v1_0(&self) -> Lossy<v1::index::Index> {
+        let index = self.clone();
+        let mut dropped = Vec::new();
+        if index.artifact_type.is_some() {
+            dropped.push("/artifactType".to_string());
+        }
+        let manifests = index
+            .manifests
+            .into_iter()
+            .enumerate()
+            .map(|(i, manifest)| {
+                manifest.strip_v1_1(&pointer("/manifests", &i.to_string()), &mut dropped)
+            })
+            .collect();
+        if index.subject.is_some() {
+            dropped.push("/subject".to_string());
+        }
+        Lossy {
+            value: v1::index::Index {
+                schema_version: index.schema_version,
+                media_type: index.media_type,
+                artifact_type: None,
+                manifests,
+                subject: None,
+                annotations: index.annotations,
+            },
+            dropped,
+        }
+    }
+}
+
+impl TryFrom<v1::index::Index> for Index {
+    type Error = Error;
+
+    /// try_from fails if a descriptor of the v1 index has no media type or digest.
+    fn try_from(index: v1::index::Index) -> Result<Self, Self::Error> {
+        Ok(Index {
+            schema_version: index.schema_version,
+            media_type: index.media_type,
+            artifact_type: index.artifact_type,
+            manifests: index
+                .manifests
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            subject: index.subject.map(TryInto::try_into).transpose()?,
+            annotations: index.annotations,
+        })
+    }
+}
+
+impl From<Index> for v1::index::Index {
+    fn from(index: Index) -> Self {
+        v1::index::Index {
+            schema_version: index.schema_version,
+            media_type: index.media_type,
+            artifact_type: index.artifact_type,
+            manifests: index.manifests.into_iter().map(Into::into).collect(),
+            subject: index.subject.map(Into::into),
+            annotations: index.annotations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_conversion() {
+        let digest = format!("sha256:{}", "a".repeat(64));
+        let descriptor = |artifact_type: Option<&str>| v1::descriptor::Descriptor {
+            media_type: Some("application/vnd.oci.image.manifest.v1+json".to_string()),
+            digest: Some(digest.parse().unwrap()),
+            size: 10,
+            artifact_type: artifact_type.map(str::to_string),
+            ..Default::default()
+        };
+        let v1 = v1::index::Index {
+            schema_version: 2,
+            manifests: vec![
+                descriptor(None),
+                descriptor(Some("application/vnd.example")),
+            ],
+            ..Default::default()
+        };
+
+        let index = Index::try_from(v1.clone()).unwrap();
+        assert_eq!(v1::index::Index::from(index.clone()), v1);
+        let v1_0 = index.to_v1_0();
+        assert_eq!(v1_0.dropped, vec!["/manifests/1/artifactType"]);
+        assert_eq!(v1_0.value.manifests[1], descriptor(None));
+        assert!(Index::try_from(v1::index::Index {
+            subject: Some(v1::descriptor::Descriptor::default()),
+            ..v1
+        })
+        .is_err());
+
+        let index = Index {
+            manifests: vec![],
+            ..index
+        };
+        assert!(index.to_v1_0().is_lossless());
+    }
+}
@@ -0,0 +1,145 @@
+use super::descriptor::Descriptor;
+use super::Lossy;
+use crate::error::Error;
+use crate::specs::v1;
+use crate::specs::v1::validation::pointer;
+
+/// Manifest is the image-spec v1.1 image manifest, whose descriptors are v1.1
+/// descriptors.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    /// SchemaVersion is the image manifest schema that this image follows
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: isize,
+
+    /// MediaType specificies the type of this document data structure e.g. `application/vnd.oci.image.manifest.v1+json`
+    #[serde(rename = "mediaType", skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+
+    /// ArtifactType is the IANA media type of the artifact this manifest describes.
+    #[serde(rename = "artifactType", skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+
+    /// Config references a configuration object for a container, by digest.
+    #[serde(rename = "config")]
+    pub config: Descriptor,
+
+    /// Layers is an indexed list of layers referenced by the manifest.
+    #[serde(rename = "layers")]
+    pub layers: Vec<Descriptor>,
+
+    /// Subject is an optional link from the image manifest to another manifest,
+    /// forming an association between the image manifest and the other manifest.
+    #[serde(rename = "subject", skip_serializing_if = "Option::is_none")]
+    pub subject: Option<Descriptor>,
+
+    /// Annotations contains arbitrary metadata for the image manifest.
+    #[serde(rename = "annotations", skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<std::collections::HashMap<String, String>>,
+}
+
+impl Manifest {
+    /// to_v1_0 returns the manifest without the fields image-spec v1.0
+    /// doesn't define, `artifactType`, `subject` and the v1.1 fields of its
+    /// descriptors, reporting those it drops.
+    pub fn to_v1_0(&self) -> Lossy<v1::manifest::Manifest> {
+        let manifest = self.clone();
+        let mut dropped = Vec::new();
+        if manifest.artifact_type.is_some() {
+            dropped.push("/artifactType".to_string());
+        }
+        let config = manifest.config.strip_v1_1("/config", &mut dropped);
+        let layers = manifest
+            .layers
+            .into_iter()
+            .enumerate()
+            .map(|(i, layer)| layer.strip_v1_1(&pointer("/layers", &i.to_string()), &mut dropped))
+            .collect();
+        if manifest.subject.is_some() {
+            dropped.push("/subject".to_string());
+        }
+        Lossy {
+            value: v1::manifest::Manifest {
+                schema_version: manifest.schema_version,
+                media_type: manifest.media_type,
+                artifact_type: None,
+                config,
+                layers,
+                subject: None,
+                annotations: manifest.annotations,
+            },
+            dropped,
+        }
+    }
+}
+
+impl TryFrom<v1::manifest::Manifest> for Manifest {
+    type Error = Error;
+
+    /// try_from fails if a descriptor of the v1 manifest has no media type or digest.
+    fn try_from(manifest: v1::manifest::Manifest) -> Result<Self, Self::Error> {
+        Ok(Manifest {
+            schema_version: manifest.schema_version,
+            media_type: manifest.media_type,
+            artifact_type: manifest.artifact_type,
+            config: manifest.config.try_into()?,
+            layers: manifest
+                .layers
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            subject: manifest.subject.map(TryInto::try_into).transpose()?,
+            annotations: manifest.annotations,
+        })
+    }
+}
+
+impl From<Manifest> for v1::manifest::Manifest {
+    fn from(manifest: Manifest) -> Self {
+        v1::manifest::Manifest {
+            schema_version: manifest.schema_version,
+            media_type: manifest.media_type,
+            artifact_type: manifest.artifact_type,
+            config: manifest.config.into(),
+            layers: manifest.layers.into_iter().map(Into::into).collect(),
+            subject: manifest.subject.map(Into::into),
+            annotations: manifest.annotations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_conversion() {
+        let manifest: Manifest = serde_json::from_str(
+            r#"{
+                "schemaVersion": 2,
+                "artifactType": "application/vnd.example.sbom",
+                "config": {"mediaType": "application/vnd.oci.empty.v1+json", "digest": "sha256:44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a", "size": 2, "data": "e30="},
+                "layers": [{"mediaType": "application/spdx+json", "digest": "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824", "size": 5}],
+                "subject": {"mediaType": "application/vnd.oci.image.manifest.v1+json", "digest": "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824", "size": 5}
+            }"#,
+        )
+        .unwrap();
+
+        let v1: v1::manifest::Manifest = manifest.clone().into();
+        assert_eq!(v1.artifact_type, manifest.artifact_type);
+        assert_eq!(v1.subject.as_ref().map(|subject| subject.size), Some(5));
+        assert_eq!(Manifest::try_from(v1.clone()).unwrap(), manifest);
+
+        let v1_0 = manifest.to_v1_0();
+        assert_eq!(
+            v1_0.dropped,
+            vec!["/artifactType", "/config/data", "/subject"]
+        );
+        assert_eq!(v1_0.value.subject, None);
+        assert_eq!(v1_0.value.layers, v1.layers);
+
+        let mut v1 = v1;
+        v1.layers[0].digest = None;
+        assert!(Manifest::try_from(v1).is_err());
+    }
+}
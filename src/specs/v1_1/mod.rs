@@ -0,0 +1,21 @@
+pub mod descriptor;
+pub mod index;
+pub mod manifest;
+
+/// Lossy is the result of a conversion which can drop fields: the converted
+/// value and the JSON pointers of the fields which were dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lossy<T> {
+    /// Value is the converted value.
+    pub value: T,
+
+    /// Dropped holds the JSON pointers of the dropped fields, e.g. `/subject`.
+    pub dropped: Vec<String>,
+}
+
+impl<T> Lossy<T> {
+    /// is_lossless reports whether the conversion kept every field.
+    pub fn is_lossless(&self) -> bool {
+        self.dropped.is_empty()
+    }
+}
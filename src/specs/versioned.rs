@@ -3,7 +3,7 @@
 /// struct to check the version.
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Versioned {
-    // SchemaVersion is the image manifest schema that this image follows
-    #[serde(rename = "SchemaVersion")]
+    // schemaVersion is the image manifest schema that this image follows
+    #[serde(rename = "schemaVersion")]
     pub schema_version: isize,
 }
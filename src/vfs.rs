@@ -0,0 +1,96 @@
+use std::io::{Read, Result};
+use std::path::Path;
+
+/// Vfs is the file system seen by the helpers reading and writing files, such
+/// as the image layout ones, so that tests can run them against memory.
+pub trait Vfs {
+    /// open returns a reader of the file at path.
+    fn open(&self, path: &Path) -> Result<Box<dyn Read + '_>>;
+
+    /// write creates or replaces the file at path with contents, creating the
+    /// missing parent directories.
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()>;
+
+    /// read returns the contents of the file at path.
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        let mut contents = Vec::new();
+        self.open(path)?.read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+}
+
+/// OsFs is the Vfs of the operating system file system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsFs;
+
+impl Vfs for OsFs {
+    fn open(&self, path: &Path) -> Result<Box<dyn Read + '_>> {
+        Ok(Box::new(std::fs::File::open(path)?))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)
+    }
+}
+
+/// MemoryFs is a Vfs holding its files in memory, for deterministic tests.
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    files: std::sync::Mutex<std::collections::BTreeMap<std::path::PathBuf, Vec<u8>>>,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MemoryFs {
+    /// new returns an empty file system.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// paths returns the paths of the files, in ascending order.
+    pub fn paths(&self) -> Vec<std::path::PathBuf> {
+        self.files.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Vfs for MemoryFs {
+    fn open(&self, path: &Path) -> Result<Box<dyn Read + '_>> {
+        match self.files.lock().unwrap().get(path) {
+            Some(contents) => Ok(Box::new(std::io::Cursor::new(contents.clone()))),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} not found", path.display()),
+            )),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_fs() {
+        let fs = MemoryFs::new();
+        assert_eq!(
+            fs.read(Path::new("/a")).unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+        fs.write(Path::new("/b/c"), b"hello").unwrap();
+        fs.write(Path::new("/a"), b"").unwrap();
+        assert_eq!(fs.read(Path::new("/b/c")).unwrap(), b"hello");
+        assert_eq!(fs.paths(), vec![Path::new("/a"), Path::new("/b/c")]);
+    }
+}